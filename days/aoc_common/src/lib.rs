@@ -0,0 +1,229 @@
+//! Helpers shared by more than one day's puzzle solution.
+
+/// Parse a line of the form `x,y,z` into a point. Whitespace
+/// around the line and around each coordinate is ignored.
+///
+/// Returns None if the line is not three comma separated,
+/// non-negative integers.
+///
+pub fn parse_point3(line: &str) -> Option<(i64, i64, i64)> {
+    let parts: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+    if 3 != parts.len() {
+        return None;
+    }
+    let mut coords: Vec<i64> = Vec::new();
+    for part in parts {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        coords.push(part.parse::<i64>().ok()?);
+    }
+    Some((coords[0], coords[1], coords[2]))
+}
+
+/// Parse a line of the form `x,y,z`, optionally prefixed by a
+/// label and a colon, e.g. `A: 1,2,3`, into the label and the
+/// point. Whitespace around the label is ignored.
+///
+/// Returns None if the point does not parse as by parse_point3,
+/// or if the label is empty.
+///
+pub fn parse_labeled_point3(
+    line: &str,
+) -> Option<(Option<String>, (i64, i64, i64))> {
+    match line.split_once(':') {
+        None => Some((None, parse_point3(line)?)),
+        Some((label, point)) => {
+            let label = label.trim();
+            if label.is_empty() {
+                return None;
+            }
+            Some((Some(label.to_string()), parse_point3(point)?))
+        }
+    }
+}
+
+/// Disjoint sets of junction box ids, used to assemble circuits
+/// by repeatedly joining the sets containing the closest pairs.
+///
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    component_count: usize,
+}
+
+impl UnionFind {
+    /// Constructor; each junction box starts in its own set.
+    ///
+    pub fn new(jb_count: usize) -> Self {
+        let parent: Vec<usize> = (0..jb_count).collect();
+        let size: Vec<usize> = vec![1; jb_count];
+        UnionFind {
+            parent,
+            size,
+            component_count: jb_count,
+        }
+    }
+
+    /// The number of disjoint sets.
+    ///
+    pub fn component_count(&self) -> usize {
+        self.component_count
+    }
+
+    /// Find the representative id of the set containing the box.
+    ///
+    pub fn find(&mut self, junction_box_id: usize) -> usize {
+        let mut root = junction_box_id;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // compress the path to the root
+        //
+        let mut id = junction_box_id;
+        while self.parent[id] != root {
+            let next = self.parent[id];
+            self.parent[id] = root;
+            id = next;
+        }
+        root
+    }
+
+    /// Join the sets containing the two boxes.
+    ///
+    /// Returns true if the boxes were in different sets.
+    ///
+    pub fn union(&mut self, id_a: usize, id_b: usize) -> bool {
+        let root_a = self.find(id_a);
+        let root_b = self.find(id_b);
+        if root_a == root_b {
+            return false;
+        }
+        let (big, small) = if self.size[root_a] >= self.size[root_b] {
+            (root_a, root_b)
+        } else {
+            (root_b, root_a)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        self.component_count -= 1;
+        true
+    }
+
+    /// Add a new junction box in a set of its own.
+    ///
+    /// Returns the id of the new box.
+    ///
+    pub fn add(&mut self) -> usize {
+        let id = self.parent.len();
+        self.parent.push(id);
+        self.size.push(1);
+        self.component_count += 1;
+        id
+    }
+
+    /// The sizes of each set, in descending order.
+    ///
+    pub fn component_sizes(&mut self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = Vec::new();
+        for id in 0..self.parent.len() {
+            if self.find(id) == id {
+                sizes.push(self.size[id]);
+            }
+        }
+        sizes.sort_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+/// Connect the first `upto` of the pairs, which are sorted by
+/// ascending distance. A pair already in the same circuit still
+/// counts as a connection.
+///
+/// Returns the sizes of all the circuits, including single boxes,
+/// in descending order.
+///
+pub fn assemble_circuits(
+    upto: usize,
+    sorted_pairs: &[(usize, usize)],
+    box_count: usize,
+) -> Vec<usize> {
+    let mut circuits = UnionFind::new(box_count);
+    for (id_a, id_b) in sorted_pairs.iter().take(upto) {
+        circuits.union(*id_a, *id_b);
+    }
+    circuits.component_sizes()
+}
+
+/// The day 8 example junction boxes, one `x,y,z` per line, for
+/// the tests of every solution to day 8.
+///
+pub const DAY08_EXAMPLE: &str = "162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,689";
+
+/// The circuit sizes, including single boxes, in descending order,
+/// after connecting the ten closest pairs of DAY08_EXAMPLE: one of
+/// 5, one of 4, two of 2 and seven singles.
+///
+pub const DAY08_EXAMPLE_SIZES_AFTER_10: [usize; 11] =
+    [5, 4, 2, 2, 1, 1, 1, 1, 1, 1, 1];
+
+#[test]
+fn assemble_circuits_counts_redundant_connections() {
+    // the third connection joins boxes already in one circuit
+    //
+    let sorted_pairs = vec![(0, 1), (1, 2), (0, 2), (3, 4)];
+    assert_eq!(vec![3, 1, 1], assemble_circuits(3, &sorted_pairs, 5));
+    assert_eq!(vec![3, 2], assemble_circuits(4, &sorted_pairs, 5));
+}
+
+#[test]
+fn parse_point3_valid() {
+    assert_eq!(Some((162, 817, 812)), parse_point3("162,817,812"));
+    assert_eq!(Some((1, 2, 3)), parse_point3("  1 , 2,3 "));
+}
+
+#[test]
+fn parse_point3_invalid() {
+    assert_eq!(None, parse_point3(""));
+    assert_eq!(None, parse_point3("1,2"));
+    assert_eq!(None, parse_point3("1,2,3,4"));
+    assert_eq!(None, parse_point3("1,,3"));
+    assert_eq!(None, parse_point3("1,-2,3"));
+    assert_eq!(None, parse_point3("1,2,x"));
+}
+
+#[test]
+fn parse_labeled_point3_with_and_without_label() {
+    assert_eq!(
+        Some((Some("A".to_string()), (1, 2, 3))),
+        parse_labeled_point3("A: 1,2,3")
+    );
+    assert_eq!(
+        Some((Some("box 7".to_string()), (4, 5, 6))),
+        parse_labeled_point3("  box 7 :4,5,6")
+    );
+    assert_eq!(Some((None, (1, 2, 3))), parse_labeled_point3("1,2,3"));
+    assert_eq!(None, parse_labeled_point3(": 1,2,3"));
+    assert_eq!(None, parse_labeled_point3("A: 1,2"));
+    assert_eq!(None, parse_labeled_point3("A: B: 1,2,3"));
+}
@@ -0,0 +1,433 @@
+//! The safe's dial, shared by the day01 binary and anything else
+//! that wants to turn it.
+
+/// A safe's dial, numbered 0 up to its length, that counts how
+/// many times it reaches zero as it turns.
+///
+#[derive(Debug)]
+pub struct Dial {
+    zero_count: u32,
+    position: u32,
+    len: u32,
+    // signed clicks turned; right is positive, left is negative
+    net_clicks: i64,
+    // clicks turned in either direction
+    total_clicks: u64,
+    // turns ending on each position; empty unless tracking visits
+    visit_counts: Vec<u32>,
+    // the position after each turn; None unless tracking stops
+    stops: Option<Vec<u32>>,
+}
+
+impl Dial {
+    /// A dial of len positions, starting halfway around
+    ///
+    pub fn new(len: u32) -> Self {
+        Self::new_at(len, len / 2, false)
+    }
+
+    /// A dial of len positions, starting at position start. If
+    /// start_on_zero_counts and the dial starts at zero, the
+    /// starting position counts as the first zero.
+    ///
+    pub fn new_at(
+        len: u32,
+        start: u32,
+        start_on_zero_counts: bool,
+    ) -> Self {
+        let position = start % len;
+        let zero_count = if start_on_zero_counts && position == 0 {
+            1
+        } else {
+            0
+        };
+        Self {
+            zero_count,
+            position,
+            len,
+            net_clicks: 0,
+            total_clicks: 0,
+            visit_counts: Vec::new(),
+            stops: None,
+        }
+    }
+
+    /// A dial of 100 positions, starting at 50
+    ///
+    pub fn new_default() -> Self {
+        Self::new(100)
+    }
+
+    /// The position the dial points at
+    ///
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// The number of times the dial has reached zero
+    ///
+    pub fn zero_count(&self) -> u32 {
+        self.zero_count
+    }
+
+    /// The signed number of clicks turned so far, counting right
+    /// turns as positive and left turns as negative
+    ///
+    pub fn net_clicks(&self) -> i64 {
+        self.net_clicks
+    }
+
+    /// The number of clicks turned so far in either direction
+    ///
+    pub fn total_clicks(&self) -> u64 {
+        self.total_clicks
+    }
+
+    /// Start counting the turns that end on each position. Off by
+    /// default to keep turns cheap.
+    ///
+    pub fn track_visits(&mut self) {
+        self.visit_counts = vec![0; self.len as usize];
+    }
+
+    /// The number of turns that ended on each position, indexed by
+    /// position; empty unless tracking visits
+    ///
+    pub fn visits(&self) -> &[u32] {
+        &self.visit_counts
+    }
+
+    /// Start recording the position the dial stops at after each
+    /// turn, for password. Off by default, since the record grows
+    /// with every turn.
+    ///
+    pub fn track_stops(&mut self) {
+        self.stops.get_or_insert_with(Vec::new);
+    }
+
+    /// The positions the dial stopped at after each turn, written
+    /// one after another, e.g. "8201" for stops at 82, 0 and 1;
+    /// empty before the first turn or unless tracking stops
+    ///
+    pub fn password(&self) -> String {
+        self.stops
+            .iter()
+            .flatten()
+            .map(|position| position.to_string())
+            .collect()
+    }
+
+    fn record_visit(&mut self) {
+        if let Some(count) =
+            self.visit_counts.get_mut(self.position as usize)
+        {
+            *count += 1;
+        }
+    }
+
+    /// Turn the dial by clicks: left (toward lower numbers) if
+    /// negative, right (toward higher numbers) if positive. Every
+    /// time the dial reaches zero along the way counts, not just
+    /// where it stops.
+    ///
+    /// Whole turns of the dial are split off first, so any i64
+    /// turns without overflowing; the counts saturate instead.
+    ///
+    pub fn turn(&mut self, clicks: i64) {
+        self.net_clicks = self.net_clicks.saturating_add(clicks);
+        let magnitude = clicks.unsigned_abs();
+        self.total_clicks = self.total_clicks.saturating_add(magnitude);
+        let len = u64::from(self.len);
+        let position = u64::from(self.position);
+        let whole_turns = magnitude / len;
+        let d = magnitude % len;
+        // past the whole turns, a right turn reaches zero if it
+        // runs off the end of the dial, and a left turn if it
+        // reaches zero from off of it
+        //
+        let (crossings, position) = if clicks < 0 {
+            let reaches_zero = (0 != position) && (d >= position);
+            (
+                whole_turns + u64::from(reaches_zero),
+                (position + len - d) % len,
+            )
+        } else {
+            (whole_turns + (position + d) / len, (position + d) % len)
+        };
+        self.zero_count = self.zero_count.saturating_add(
+            u32::try_from(crossings).unwrap_or(u32::MAX),
+        );
+        self.position = position as u32;
+        if let Some(stops) = self.stops.as_mut() {
+            stops.push(self.position);
+        }
+        self.record_visit();
+    }
+
+    /// Turn the dial clicks toward lower numbers
+    ///
+    pub fn left(&mut self, clicks: u32) {
+        self.turn(-i64::from(clicks));
+    }
+
+    /// Turn the dial clicks toward higher numbers
+    ///
+    pub fn right(&mut self, clicks: u32) {
+        self.turn(i64::from(clicks));
+    }
+}
+
+// left tests
+//
+#[test]
+fn check_left_before_zero() {
+    let mut dial = Dial::new_default();
+    dial.left(49);
+    assert_eq!(dial.position, 1);
+    assert_eq!(dial.zero_count, 0);
+}
+
+#[test]
+fn check_left_to_zero() {
+    let mut dial = Dial::new_default();
+    dial.left(50);
+    assert_eq!(dial.position, 0);
+    assert_eq!(dial.zero_count, 1);
+}
+
+#[test]
+fn check_left_beyond_zero() {
+    let mut dial = Dial::new_default();
+    dial.left(55);
+    assert_eq!(dial.position, 95);
+    assert_eq!(dial.zero_count, 1);
+}
+
+#[test]
+fn check_left_before_zero_wrapped() {
+    let mut dial = Dial::new_default();
+    dial.left(349);
+    assert_eq!(dial.position, 1);
+    assert_eq!(dial.zero_count, 3);
+}
+
+#[test]
+fn check_left_to_zero_wrapped() {
+    let mut dial = Dial::new_default();
+    dial.left(250);
+    assert_eq!(dial.position, 0);
+    assert_eq!(dial.zero_count, 3);
+}
+
+#[test]
+fn check_left_beyond_zero_wrapped() {
+    let mut dial = Dial::new_default();
+    dial.left(155);
+    assert_eq!(dial.position, 95);
+    assert_eq!(dial.zero_count, 2);
+}
+
+#[test]
+fn check_left_crossings() {
+    let mut dial = Dial::new_default();
+    dial.left(300);
+    assert_eq!(dial.position, 50);
+    assert_eq!(dial.zero_count, 3);
+    let mut dial = Dial::new_at(100, 0, false);
+    dial.left(100);
+    assert_eq!(dial.position, 0);
+    assert_eq!(dial.zero_count, 1);
+}
+
+// right tests
+//
+#[test]
+fn check_right_before_zero() {
+    let mut dial = Dial::new_default();
+    dial.right(49);
+    assert_eq!(dial.position, 99);
+    assert_eq!(dial.zero_count, 0);
+}
+
+#[test]
+fn check_right_to_zero() {
+    let mut dial = Dial::new_default();
+    dial.right(50);
+    assert_eq!(dial.position, 0);
+    assert_eq!(dial.zero_count, 1);
+}
+
+#[test]
+fn check_right_beyond_zero() {
+    let mut dial = Dial::new_default();
+    dial.right(55);
+    assert_eq!(dial.position, 5);
+    assert_eq!(dial.zero_count, 1);
+}
+
+#[test]
+fn check_right_before_zero_wrapped() {
+    let mut dial = Dial::new_default();
+    dial.right(349);
+    assert_eq!(dial.position, 99);
+    assert_eq!(dial.zero_count, 3);
+}
+
+#[test]
+fn check_right_to_zero_wrapped() {
+    let mut dial = Dial::new_default();
+    dial.right(250);
+    assert_eq!(dial.position, 0);
+    assert_eq!(dial.zero_count, 3);
+}
+
+#[test]
+fn check_right_beyond_zero_wrapped() {
+    let mut dial = Dial::new_default();
+    dial.right(155);
+    assert_eq!(dial.position, 5);
+    assert_eq!(dial.zero_count, 2);
+}
+
+#[test]
+fn check_right_crossings() {
+    let mut dial = Dial::new_default();
+    dial.right(1000);
+    assert_eq!(dial.position, 50);
+    assert_eq!(dial.zero_count, 10);
+    dial.right(1049);
+    assert_eq!(dial.position, 99);
+    assert_eq!(dial.zero_count, 20);
+    dial.right(u32::MAX);
+    assert_eq!(dial.position, 94);
+    assert_eq!(dial.zero_count, 20 + 42949673);
+}
+
+// click tracking tests
+//
+#[test]
+fn check_clicks_after_mixed_turns() {
+    let mut dial = Dial::new_default();
+    assert_eq!(dial.net_clicks(), 0);
+    assert_eq!(dial.total_clicks(), 0);
+    dial.left(68);
+    dial.right(30);
+    dial.left(155);
+    dial.right(48);
+    assert_eq!(dial.net_clicks(), -145);
+    assert_eq!(dial.total_clicks(), 301);
+}
+
+// signed turn tests
+//
+#[test]
+fn check_turn_matches_left_and_right() {
+    let turns: [i64; 8] = [
+        1_000_003,
+        -2_000_017,
+        999,
+        -1,
+        4_000_000_000,
+        -4_000_000_050,
+        -50,
+        100,
+    ];
+    let mut signed = Dial::new_default();
+    let mut unsigned = Dial::new_default();
+    for clicks in turns {
+        signed.turn(clicks);
+        if clicks < 0 {
+            unsigned.left(clicks.unsigned_abs() as u32);
+        } else {
+            unsigned.right(clicks as u32);
+        }
+        assert_eq!(unsigned.position, signed.position);
+        assert_eq!(unsigned.zero_count, signed.zero_count);
+    }
+    assert_eq!(unsigned.net_clicks(), signed.net_clicks());
+    assert_eq!(unsigned.total_clicks(), signed.total_clicks());
+}
+
+#[test]
+fn check_turn_extremes() {
+    // 2^63 is 9223372036854775808, which leaves 8 from 100
+    //
+    let mut dial = Dial::new_default();
+    dial.turn(i64::MIN);
+    assert_eq!(dial.position, 42);
+    assert_eq!(dial.zero_count, u32::MAX);
+    let mut dial = Dial::new_default();
+    dial.turn(i64::MAX);
+    assert_eq!(dial.position, 57);
+    assert_eq!(dial.zero_count, u32::MAX);
+    dial.turn(i64::MAX);
+    assert_eq!(dial.net_clicks(), i64::MAX);
+    assert_eq!(dial.position, 64);
+}
+
+// starting position tests
+//
+#[test]
+fn check_start_len_100() {
+    let dial = Dial::new_default();
+    assert_eq!(50, dial.position);
+    assert_eq!(0, dial.zero_count);
+}
+
+#[test]
+fn check_start_len_10() {
+    let mut dial = Dial::new(10);
+    assert_eq!(5, dial.position);
+    dial.left(5);
+    assert_eq!(0, dial.position);
+    assert_eq!(1, dial.zero_count);
+}
+
+#[test]
+fn check_start_at_zero() {
+    let mut dial = Dial::new_at(100, 0, false);
+    assert_eq!(0, dial.position);
+    assert_eq!(0, dial.zero_count);
+    dial.right(10);
+    assert_eq!(10, dial.position);
+    assert_eq!(0, dial.zero_count);
+
+    let dial = Dial::new_at(100, 0, true);
+    assert_eq!(0, dial.position);
+    assert_eq!(1, dial.zero_count);
+
+    // only a start exactly on zero counts
+    //
+    let dial = Dial::new_at(100, 30, true);
+    assert_eq!(30, dial.position);
+    assert_eq!(0, dial.zero_count);
+}
+
+// password tests
+//
+#[test]
+fn check_password() {
+    let dial = Dial::new_default();
+    assert_eq!("", dial.password());
+    assert_eq!(50, dial.position());
+
+    let mut dial = Dial::new_default();
+    dial.left(68);
+    assert_eq!("", dial.password());
+    dial.track_stops();
+    dial.left(30);
+    dial.right(48);
+    dial.turn(-5);
+    dial.right(60);
+    assert_eq!("5209555", dial.password());
+    assert_eq!(55, dial.position());
+
+    let mut dial = Dial::new_default();
+    dial.track_stops();
+    dial.left(68);
+    dial.left(30);
+    dial.right(48);
+    dial.turn(-5);
+    dial.right(60);
+    assert_eq!("825209555", dial.password());
+}
@@ -1,9 +1,12 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
+use std::sync::LazyLock;
 
 use anyhow::{Context, Result};
 use clap::Parser;
+use day01::Dial;
 use regex::Regex;
 
 /// Given input file containing the safe dial operations,
@@ -11,77 +14,190 @@ use regex::Regex;
 ///
 #[derive(Parser)]
 struct Cli {
+    /// Print how many times the dial came to rest on each
+    /// position
+    #[arg(long = "visits")]
+    visits: bool,
+    /// Print the derived password: the positions the dial stopped
+    /// at after each turn, one after another
+    #[arg(long = "derived-password")]
+    derived_password: bool,
+    /// Warn on stderr, and exit with a failure status, if the dial
+    /// never reaches zero; often a sign the input was misparsed
+    #[arg(long = "warn-no-zero")]
+    warn_no_zero: bool,
     /// The path to the file containing dial operations
     path: PathBuf,
 }
 
-#[derive(Debug)]
-struct Dial {
-    zero_count: u32,
-    position: u32,
-    len: u32,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Left,
+    Right,
 }
 
-impl Dial {
-    fn new(len: u32) -> Self {
-        Self {
-            zero_count: 0,
-            position: 50,
-            len: len,
-        }
-    }
+#[derive(Debug, PartialEq)]
+enum ParseErrorReason {
+    MissingDirection,
+    MissingClicks,
+    ClicksNotANumber,
+}
 
-    fn new_default() -> Self {
-        Self::new(100)
+// A failure to parse a dial instruction, with the byte offset
+// within the line where the problem was found.
+//
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    offset: usize,
+    reason: ParseErrorReason,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.reason {
+            ParseErrorReason::MissingDirection => "missing direction",
+            ParseErrorReason::MissingClicks => "missing clicks",
+            ParseErrorReason::ClicksNotANumber => "clicks not a number",
+        };
+        write!(f, "{} at byte {}", reason, self.offset)
     }
+}
 
-    fn left(&mut self, clicks: u32) {
-        let d = clicks % self.len;
-        let wrap_count = (clicks - d) / self.len;
-        // if d != clicks {
-        //     println!("Found clicks > 99!!");
-        // }
-        if d <= self.position {
-            self.position -= d;
-        } else {
-            if self.position != 0 {
-                self.zero_count += 1; // passed zero
+impl std::error::Error for ParseError {}
+
+// A dial instruction: a direction and a number of clicks, e.g.
+// "L68". The direction may also be spelled out, in any case, and
+// only then separated from the clicks by whitespace, e.g.
+// "right 30".
+//
+static INSTRUCTION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:(?i:left|right)\s*|[LR])([0-9]+)").unwrap()
+});
+
+// Parse a line into dial instructions, each matching
+// INSTRUCTION_RE. A line may hold several instructions, run
+// together or separated by whitespace, e.g. "L5 R10L3"; a blank
+// line holds none.
+//
+// Returns an error for the first part of the line, other than
+// whitespace, that no instruction matches, so none of a bad
+// line's instructions are taken.
+//
+fn parse_instruction(
+    line: &str,
+) -> Result<Vec<(Direction, u32)>, ParseError> {
+    let mut instructions: Vec<(Direction, u32)> = Vec::new();
+    // the end of the last instruction matched, or zero
+    let mut covered = 0;
+    for caps in INSTRUCTION_RE.captures_iter(line) {
+        let whole = caps.get(0).unwrap();
+        check_uncovered(line, covered, whole.start())?;
+        let (dir, _) = direction_at(whole.as_str()).unwrap();
+        let clicks = caps.get(1).unwrap();
+        let clicks = match clicks.as_str().parse::<u32>() {
+            Ok(n) => n,
+            Err(_) => {
+                return Err(ParseError {
+                    offset: clicks.start(),
+                    reason: ParseErrorReason::ClicksNotANumber,
+                });
             }
-            self.position = self.len + self.position - d;
-        }
-        if self.position == 0 {
-            self.zero_count += 1;
+        };
+        instructions.push((dir, clicks));
+        covered = whole.end();
+    }
+    check_uncovered(line, covered, line.len())?;
+    Ok(instructions)
+}
+
+// Check that the part of the line from start to end, which no
+// instruction matched, is only whitespace; otherwise say why no
+// instruction starts where it stops being whitespace.
+//
+fn check_uncovered(
+    line: &str,
+    start: usize,
+    end: usize,
+) -> Result<(), ParseError> {
+    let gap = &line[start..end];
+    let pos = start + (gap.len() - gap.trim_start().len());
+    if pos == end {
+        return Ok(());
+    }
+    let rest = &line[pos..];
+    let (_, dir_len) = match direction_at(rest) {
+        // right after an instruction, the clicks run on
+        //
+        None if (0 < pos) && (pos == start) => {
+            return Err(ParseError {
+                offset: pos,
+                reason: ParseErrorReason::ClicksNotANumber,
+            });
         }
-        self.zero_count += wrap_count;
-        // println!(
-        //     "L{}; {} at position {}",
-        //     clicks, self.zero_count, self.position
-        // );
-    }
-
-    fn right(&mut self, clicks: u32) {
-        let d = clicks % self.len;
-        let wrap_count = (clicks - d) / self.len;
-        // if d != clicks {
-        //     println!("Found clicks > 99!!");
-        // }
-        if d <= (self.len - self.position) {
-            self.position += d;
-        } else {
-            if self.position != 0 {
-                self.zero_count += 1; // passed zero
-            }
-            self.position += d;
+        None => {
+            return Err(ParseError {
+                offset: pos,
+                reason: ParseErrorReason::MissingDirection,
+            });
         }
-        self.position %= self.len;
-        if self.position == 0 {
-            self.zero_count += 1;
+        Some(found) => found,
+    };
+    // only a spelled out direction may be followed by whitespace
+    //
+    let mut clicks_start = pos + dir_len;
+    if 1 < dir_len {
+        let after = &line[clicks_start..];
+        clicks_start += after.len() - after.trim_start().len();
+    }
+    let after = &line[clicks_start..];
+    if after.is_empty()
+        || after.starts_with(char::is_whitespace)
+        || direction_at(after).is_some()
+    {
+        return Err(ParseError {
+            offset: pos + dir_len,
+            reason: ParseErrorReason::MissingClicks,
+        });
+    }
+    Err(ParseError {
+        offset: clicks_start,
+        reason: ParseErrorReason::ClicksNotANumber,
+    })
+}
+
+// The direction the text starts with, and how many bytes it takes
+// up, if it starts with one
+//
+fn direction_at(text: &str) -> Option<(Direction, usize)> {
+    let starts_with_word = |word: &str| {
+        text.get(..word.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(word))
+    };
+    if starts_with_word("left") {
+        Some((Direction::Left, "left".len()))
+    } else if starts_with_word("right") {
+        Some((Direction::Right, "right".len()))
+    } else {
+        match text.chars().next() {
+            Some('L') => Some((Direction::Left, 1)),
+            Some('R') => Some((Direction::Right, 1)),
+            _ => None,
         }
-        self.zero_count += wrap_count;
-        // println!(
-        //     "R{}; {} at position {}",
-        //     clicks, self.zero_count, self.position
-        // );
+    }
+}
+
+// A warning if the dial never reached zero over the whole
+// program, which usually means the instructions were misparsed;
+// otherwise None.
+//
+fn no_zero_warning(dial: &Dial) -> Option<String> {
+    if 0 == dial.zero_count() {
+        Some(format!(
+            "the dial never reached zero in {} clicks; were the instructions parsed correctly?",
+            dial.total_clicks()
+        ))
+    } else {
+        None
     }
 }
 
@@ -96,10 +212,12 @@ fn main() -> Result<()> {
     let lines = rdr.lines();
 
     let mut dial = Dial::new_default();
-    let re_inst = Regex::new(r"([LR])([0-9]+)").unwrap();
-
-    // dial.left(1);
-    // dial.right(1);
+    if args.visits {
+        dial.track_visits();
+    }
+    if args.derived_password {
+        dial.track_stops();
+    }
 
     let mut line_num = 0;
     for line in lines {
@@ -107,127 +225,225 @@ fn main() -> Result<()> {
             format!("Problem reading from `{}`", path.display())
         })?;
         line_num += 1;
-        if !re_inst.is_match(&line) {
-            println!(
-                "*** FAILED *** to match line {}: '{}'",
-                line_num, line
-            );
-            continue;
+        let instructions = match parse_instruction(&line) {
+            Ok(instructions) => instructions,
+            Err(_) => {
+                println!(
+                    "*** FAILED *** to match line {}: '{}'",
+                    line_num, line
+                );
+                continue;
+            }
+        };
+        for (dir, dist) in instructions {
+            match dir {
+                Direction::Left => dial.left(dist),
+                Direction::Right => dial.right(dist),
+            }
         }
-        let caps = re_inst.captures(&line).unwrap();
-        let dir: &str = caps.get(1).unwrap().as_str();
-        let dist: &str = caps.get(2).unwrap().as_str();
-        let dist: u32 = dist.parse::<u32>().unwrap();
-        if "L".eq(dir) {
-            dial.left(dist);
-        } else if "R".eq(dir) {
-            dial.right(dist);
+    }
+    if args.visits {
+        for (position, count) in dial.visits().iter().enumerate() {
+            if 0 < *count {
+                println!("position {}: {} visits", position, count);
+            }
         }
-        // println!(
-        //     "Turn {} {} clicks; zero count is {}.",
-        //     dir, dist, dial.zero_count
-        // );
     }
-    println!("The password is {}.", dial.zero_count);
+    println!("The dial stopped at position {}.", dial.position());
+    println!("The dial reached zero {} times.", dial.zero_count());
+    if args.derived_password {
+        println!("The derived password is {:?}.", dial.password());
+    }
+    println!("The password is {}.", dial.zero_count());
+    if args.warn_no_zero
+        && let Some(warning) = no_zero_warning(&dial)
+    {
+        eprintln!("warning: {}", warning);
+        std::process::exit(1);
+    }
     Ok(())
 }
 
-// left tests
+// no zero warning tests
 //
 #[test]
-fn check_left_before_zero() {
-    let mut dial = Dial::new_default();
-    dial.left(49);
-    assert_eq!(dial.position, 1);
-    assert_eq!(dial.zero_count, 0);
-}
-
-#[test]
-fn check_left_to_zero() {
+fn check_no_zero_warning() {
     let mut dial = Dial::new_default();
+    dial.left(10);
+    dial.right(30);
+    dial.left(20);
+    assert_eq!(
+        Some(
+            "the dial never reached zero in 60 clicks; were the instructions parsed correctly?"
+                .to_string()
+        ),
+        no_zero_warning(&dial)
+    );
     dial.left(50);
-    assert_eq!(dial.position, 0);
-    assert_eq!(dial.zero_count, 1);
+    assert_eq!(None, no_zero_warning(&dial));
 }
 
+// visit count tests
+//
 #[test]
-fn check_left_beyond_zero() {
-    let mut dial = Dial::new_default();
-    dial.left(55);
-    assert_eq!(dial.position, 95);
-    assert_eq!(dial.zero_count, 0);
-}
+fn check_visit_counts() {
+    let mut dial = Dial::new(10);
+    dial.right(3);
+    assert!(dial.visits().is_empty());
 
-#[test]
-fn check_left_before_zero_wrapped() {
-    let mut dial = Dial::new_default();
-    dial.left(349);
-    assert_eq!(dial.position, 1);
-    assert_eq!(dial.zero_count, 0);
+    let mut dial = Dial::new(10);
+    dial.track_visits();
+    let program = [
+        (Direction::Left, 5),
+        (Direction::Right, 12),
+        (Direction::Left, 2),
+        (Direction::Right, 30),
+        (Direction::Left, 7),
+    ];
+    for (dir, clicks) in program {
+        match dir {
+            Direction::Left => dial.left(clicks),
+            Direction::Right => dial.right(clicks),
+        }
+    }
+    let visits = dial.visits();
+    assert_eq!(10, visits.len());
+    assert_eq!(program.len() as u32, visits.iter().sum::<u32>());
+    assert_eq!(vec![3, 0, 1, 1, 0, 0, 0, 0, 0, 0], visits.to_vec());
 }
 
-#[test]
-fn check_left_to_zero_wrapped() {
-    let mut dial = Dial::new_default();
-    dial.left(250);
-    assert_eq!(dial.position, 0);
-    assert_eq!(dial.zero_count, 1);
-}
+// parse tests
+//
 
 #[test]
-fn check_left_beyond_zero_wrapped() {
-    let mut dial = Dial::new_default();
-    dial.left(155);
-    assert_eq!(dial.position, 95);
-    assert_eq!(dial.zero_count, 0);
+fn parse_valid_line() {
+    let actual = parse_instruction("L68").unwrap();
+    assert_eq!(vec![(Direction::Left, 68)], actual);
+    let actual = parse_instruction("  R14 ").unwrap();
+    assert_eq!(vec![(Direction::Right, 14)], actual);
 }
 
-// right tests
-//
 #[test]
-fn check_right_before_zero() {
-    let mut dial = Dial::new_default();
-    dial.right(49);
-    assert_eq!(dial.position, 99);
-    assert_eq!(dial.zero_count, 0);
+fn parse_empty_line() {
+    assert_eq!(Ok(vec![]), parse_instruction(""));
+    assert_eq!(Ok(vec![]), parse_instruction(" \t "));
+    let actual = parse_instruction("  x");
+    let expected = ParseError {
+        offset: 2,
+        reason: ParseErrorReason::MissingDirection,
+    };
+    assert_eq!(Err(expected), actual);
 }
 
 #[test]
-fn check_right_to_zero() {
-    let mut dial = Dial::new_default();
-    dial.right(50);
-    assert_eq!(dial.position, 0);
-    assert_eq!(dial.zero_count, 1);
-}
+fn parse_several_per_line() {
+    assert_eq!(
+        vec![
+            (Direction::Left, 5),
+            (Direction::Right, 10),
+            (Direction::Left, 3)
+        ],
+        parse_instruction("L5 R10 L3 ").unwrap()
+    );
+    assert_eq!(
+        vec![
+            (Direction::Right, 2),
+            (Direction::Left, 40),
+            (Direction::Right, 1)
+        ],
+        parse_instruction("right 2L40Right1").unwrap()
+    );
+    assert_eq!(
+        Err(ParseError {
+            offset: 4,
+            reason: ParseErrorReason::MissingDirection,
+        }),
+        parse_instruction("L50 x3 R2")
+    );
+    assert_eq!(
+        Err(ParseError {
+            offset: 1,
+            reason: ParseErrorReason::MissingClicks,
+        }),
+        parse_instruction("LR5")
+    );
 
-#[test]
-fn check_right_beyond_zero() {
     let mut dial = Dial::new_default();
-    dial.right(55);
-    assert_eq!(dial.position, 5);
-    assert_eq!(dial.zero_count, 0);
+    for line in "L50R50\nL1".split('\n') {
+        for (dir, clicks) in parse_instruction(line).unwrap() {
+            match dir {
+                Direction::Left => dial.left(clicks),
+                Direction::Right => dial.right(clicks),
+            }
+        }
+    }
+    assert_eq!(49, dial.position());
+    assert_eq!(1, dial.zero_count());
 }
 
 #[test]
-fn check_right_before_zero_wrapped() {
-    let mut dial = Dial::new_default();
-    dial.right(349);
-    assert_eq!(dial.position, 99);
-    assert_eq!(dial.zero_count, 0);
+fn parse_missing_clicks() {
+    let actual = parse_instruction("R");
+    let expected = ParseError {
+        offset: 1,
+        reason: ParseErrorReason::MissingClicks,
+    };
+    assert_eq!(Err(expected), actual);
 }
 
 #[test]
-fn check_right_to_zero_wrapped() {
-    let mut dial = Dial::new_default();
-    dial.right(250);
-    assert_eq!(dial.position, 0);
-    assert_eq!(dial.zero_count, 1);
+fn parse_clicks_not_a_number() {
+    let actual = parse_instruction(" L4x2");
+    let expected = ParseError {
+        offset: 3,
+        reason: ParseErrorReason::ClicksNotANumber,
+    };
+    assert_eq!(Err(expected), actual);
 }
 
 #[test]
-fn check_right_beyond_zero_wrapped() {
-    let mut dial = Dial::new_default();
-    dial.right(155);
-    assert_eq!(dial.position, 5);
-    assert_eq!(dial.zero_count, 0);
+fn parse_direction_words() {
+    assert_eq!(
+        vec![(Direction::Right, 30)],
+        parse_instruction("right 30").unwrap()
+    );
+    assert_eq!(
+        vec![(Direction::Left, 7)],
+        parse_instruction(" LEFT7").unwrap()
+    );
+    assert_eq!(
+        Err(ParseError {
+            offset: 5,
+            reason: ParseErrorReason::MissingClicks,
+        }),
+        parse_instruction("Right ")
+    );
+    assert_eq!(
+        Err(ParseError {
+            offset: 6,
+            reason: ParseErrorReason::ClicksNotANumber,
+        }),
+        parse_instruction("left 3x")
+    );
+
+    // a mixed program turns the dial as its abbreviated equivalent
+    //
+    let mixed = ["L68", "right 30", "Left 48", "R5", "RIGHT\t60"];
+    let abbreviated = ["L68", "R30", "L48", "R5", "R60"];
+    let mut dials: Vec<Dial> = Vec::new();
+    for program in [mixed, abbreviated] {
+        let mut dial = Dial::new_default();
+        for line in program {
+            for (dir, clicks) in parse_instruction(line).unwrap() {
+                match dir {
+                    Direction::Left => dial.left(clicks),
+                    Direction::Right => dial.right(clicks),
+                }
+            }
+        }
+        dials.push(dial);
+    }
+    assert_eq!(dials[1].position(), dials[0].position());
+    assert_eq!(dials[1].zero_count(), dials[0].zero_count());
+    assert_eq!(dials[1].net_clicks(), dials[0].net_clicks());
 }
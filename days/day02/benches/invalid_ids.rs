@@ -0,0 +1,25 @@
+//! Benchmarks for generating the invalid IDs of a range. The range
+//! is fixed so that runs compare like with like.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use day02::IdRange;
+
+// IDs of up to ten digits, so patterns of up to five digits
+//
+const START: u64 = 1;
+const END: u64 = 9_999_999_999;
+
+fn bench_invalid_ids(c: &mut Criterion) {
+    let idr = IdRange::new(START, END);
+    c.bench_function("invalid_ids", |b| {
+        b.iter(|| black_box(&idr).invalid_ids().len())
+    });
+    c.bench_function("count_invalid_ids", |b| {
+        b.iter(|| black_box(&idr).count_invalid_ids())
+    });
+}
+
+criterion_group!(benches, bench_invalid_ids);
+criterion_main!(benches);
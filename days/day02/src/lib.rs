@@ -0,0 +1,404 @@
+//! Product ID ranges and the invalid IDs within them.
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// A range of product IDs, start to end inclusive
+///
+#[derive(Debug)]
+pub struct IdRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+static IDRANGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([0-9]+)-([0-9]+)\s*,?\s*$").unwrap()
+});
+
+impl IdRange {
+    /// A range from start to end, inclusive
+    ///
+    pub fn new(start: u64, end: u64) -> Self {
+        Self {
+            start: start,
+            end: end,
+        }
+    }
+
+    /// Parse a range written start-end, e.g. "11-22", allowing
+    /// whitespace around it and a trailing comma.
+    ///
+    /// Returns None, after printing a notice, if it does not parse.
+    ///
+    pub fn new_from_str(id_range: &str) -> Option<Self> {
+        if !IDRANGE_RE.is_match(id_range) {
+            println!("*** FAILED *** to match range '{}'", id_range);
+            return None;
+        }
+        let caps = IDRANGE_RE.captures(&id_range).unwrap();
+        let sstr: &str = caps.get(1).unwrap().as_str();
+        let estr: &str = caps.get(2).unwrap().as_str();
+        let start: u64 = sstr.parse::<u64>().unwrap();
+        let end: u64 = estr.parse::<u64>().unwrap();
+        Some(IdRange::new(start, end))
+    }
+
+    // Returns true if a pattern of pattern_len digits, repeated
+    // at least twice, can produce an ID with a digit count
+    // between start_len and end_len (inclusive).
+    //
+    fn has_repeat_length(
+        pattern_len: u32,
+        start_len: u32,
+        end_len: u32,
+    ) -> bool {
+        let mut id_len: u32 = pattern_len * 2;
+        while id_len <= end_len {
+            if id_len >= start_len {
+                return true;
+            }
+            id_len += pattern_len;
+        }
+        false
+    }
+
+    /// This is the Part 2 function that produces invalid IDs
+    /// within a range (inclusive)
+    ///
+    pub fn invalid_ids(&self) -> Vec<u64> {
+        let mut result: Vec<u64> = Vec::new();
+        let mut set: HashSet<u64> = HashSet::new();
+        let start_len: u32 = self.start.to_string().len() as u32;
+        let end_len: u32 = self.end.to_string().len() as u32;
+
+        // start with a pattern size of a single digit
+        //
+        let mut pattern_mag: u32 = 0;
+        loop {
+            let pattern_inc: u64 = u64::pow(10, pattern_mag) * 10;
+            let pattern_min: u64 = pattern_inc / 10;
+            // if minimum pattern value is larger than self.end
+            // then stop looking at this and larger patterns
+            //
+            if (pattern_min * pattern_inc) > self.end {
+                break;
+            }
+            // if no repetition of this pattern size has a digit
+            // count within the range, then skip this pattern size
+            //
+            if !IdRange::has_repeat_length(
+                pattern_mag + 1,
+                start_len,
+                end_len,
+            ) {
+                pattern_mag += 1;
+                continue;
+            }
+            // loop through the pattern values from pattern_inc - 1
+            // down to pattern_min and capture any repeated
+            // pattern values that are within the IdRange.
+            //
+            let mut pattern_num = pattern_inc;
+            loop {
+                pattern_num -= 1;
+                if pattern_num < pattern_min {
+                    break;
+                }
+                let mut num: u64 =
+                    (pattern_num * pattern_inc) + pattern_num;
+                while num < self.start {
+                    num = (num * pattern_inc) + pattern_num;
+                }
+                while num <= self.end {
+                    if !set.contains(&num) {
+                        result.push(num);
+                        set.insert(num);
+                    }
+                    num = (num * pattern_inc) + pattern_num;
+                }
+            }
+
+            // expand the size of the pattern
+            //
+            pattern_mag += 1;
+        }
+        return result;
+    }
+
+    /// The number of invalid IDs within the range (inclusive), the
+    /// same as invalid_ids().len() but without collecting them.
+    ///
+    /// An ID made of a pattern repeated is made of exactly one
+    /// pattern that is not itself repeated, so counting only the
+    /// repeats of such patterns counts every ID once, without a set
+    /// of those seen.
+    ///
+    pub fn count_invalid_ids(&self) -> u64 {
+        let mut count: u64 = 0;
+        let start_len: u32 = self.start.to_string().len() as u32;
+        let end_len: u32 = self.end.to_string().len() as u32;
+        let mut pattern_mag: u32 = 0;
+        loop {
+            let pattern_inc: u64 = u64::pow(10, pattern_mag) * 10;
+            let pattern_min: u64 = pattern_inc / 10;
+            if (pattern_min * pattern_inc) > self.end {
+                break;
+            }
+            if !IdRange::has_repeat_length(
+                pattern_mag + 1,
+                start_len,
+                end_len,
+            ) {
+                pattern_mag += 1;
+                continue;
+            }
+            for pattern_num in pattern_min..pattern_inc {
+                if is_repeated_pattern(pattern_num, pattern_mag + 1) {
+                    continue;
+                }
+                let mut num: u64 =
+                    (pattern_num * pattern_inc) + pattern_num;
+                while num < self.start {
+                    num = (num * pattern_inc) + pattern_num;
+                }
+                while num <= self.end {
+                    count += 1;
+                    num = (num * pattern_inc) + pattern_num;
+                }
+            }
+            pattern_mag += 1;
+        }
+        count
+    }
+
+    /// This is the Part 1 function that produced invalid
+    /// ids within a range (inclusive)
+    ///
+    pub fn invalid_ids1(&self) -> Vec<u64> {
+        let mut result: Vec<u64> = Vec::new();
+        let start_s = self.start.to_string();
+        let end_s = self.end.to_string();
+
+        // if odd number of digits and both start and end
+        // have the same magnitude, then there are no
+        // invalid IDs in the range
+        //
+        if (start_s.len() == end_s.len())
+            && (start_s.len() % 2 == 1)
+            && (end_s.len() % 2 == 1)
+        {
+            return result;
+        }
+
+        // 'num' will be the variable to hold the ID to be
+        // scanned.
+        //
+        let mut num: u64 = self.start;
+
+        // if 'num' has an odd number of digits, jump to the
+        // next power of 10
+        //
+        let s = num.to_string();
+        if s.len() % 2 == 1 {
+            // println!("wat");
+            let exp: u32 = s.len() as u32;
+            num = u64::pow(10, exp);
+        }
+
+        let mag: u32 = (num.to_string().len() as u32) - 1; // power of 10
+        let half_mag: u32 = mag / 2;
+        // println!("num: {}; mag: {}; half_mag: {}", num, mag, half_mag);
+        let mut inc: u64 = u64::pow(10, half_mag + 1);
+        let mut half_num: u64 = num / inc;
+        let mut half_num_max: u64 = u64::pow(10, half_mag + 1);
+        loop {
+            num = (half_num * inc) + half_num;
+            if num > self.end {
+                break;
+            }
+            if num >= self.start {
+                result.push(num);
+            }
+            half_num += 1;
+            // if we've jumped up to the next power of 10, then
+            // that will be an odd pairing, so we need to jump
+            // yet another power of 10 and then keep looking
+            //
+            if half_num >= half_num_max {
+                half_num = half_num_max * 10;
+                inc *= 100;
+                half_num_max *= 100;
+            }
+        }
+        return result;
+    }
+}
+
+// Returns true if the pattern, of digits digits, is itself some
+// shorter sequence of digits repeated, e.g. 1212 or 555.
+//
+fn is_repeated_pattern(pattern: u64, digits: u32) -> bool {
+    for unit_len in 1..digits {
+        if !digits.is_multiple_of(unit_len) {
+            continue;
+        }
+        let unit_inc = u64::pow(10, unit_len);
+        let unit = pattern % unit_inc;
+        let mut repeated: u64 = 0;
+        for _ in 0..(digits / unit_len) {
+            repeated = (repeated * unit_inc) + unit;
+        }
+        if repeated == pattern {
+            return true;
+        }
+    }
+    false
+}
+
+// The range to parse from the characters read since the last
+// comma: up to and including the next comma, or to the end of the
+// list.
+//
+// Returns None if there is no range to parse, i.e. the characters
+// are only whitespace and a comma, as after a trailing comma.
+//
+fn flush_token(buffer: &[char]) -> Option<String> {
+    if buffer.iter().all(|c| c.is_whitespace() || ',' == *c) {
+        return None;
+    }
+    Some(buffer.iter().collect())
+}
+
+/// Sum the invalid IDs of every range in the comma-separated list.
+/// The list may or may not end with a comma; whitespace left over
+/// after the last comma is ignored.
+///
+pub fn sum_invalid_ids<I: Iterator<Item = char>>(chars: I) -> u64 {
+    let mut invalid_id_accum: u64 = 0;
+    let mut s = Vec::new();
+    let mut chars = chars.peekable();
+    while let Some(c) = chars.next() {
+        s.push(c);
+        if c != ',' && chars.peek().is_some() {
+            continue;
+        }
+        if let Some(idr) =
+            flush_token(&s).and_then(|ss| IdRange::new_from_str(&ss))
+        {
+            for invalid_id in idr.invalid_ids() {
+                // println!("Invalid ID: {}", invalid_id);
+                invalid_id_accum += invalid_id;
+            }
+            // println!(
+            //     "Range: {}-{}; accum: {}",
+            //     idr.start, idr.end, invalid_id_accum
+            // );
+        }
+        s.clear();
+    }
+    invalid_id_accum
+}
+
+// Returns true if the ID is made only of some sequence of digits
+// repeated at least twice; a brute force check used by tests.
+//
+#[cfg(test)]
+fn testhelper_is_repeated(id: u64) -> bool {
+    let s = id.to_string();
+    let len = s.len();
+    for pattern_len in 1..=(len / 2) {
+        if len % pattern_len != 0 {
+            continue;
+        }
+        let pattern = &s[0..pattern_len];
+        if pattern.repeat(len / pattern_len) == s {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn invalid_ids_across_digit_counts_match_brute_force() {
+    let idr = IdRange::new(95, 123456);
+    let mut actual = idr.invalid_ids();
+    actual.sort();
+    let expected: Vec<u64> = (95..=123456)
+        .filter(|x| testhelper_is_repeated(*x))
+        .collect();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn invalid_ids_skips_pattern_sizes_without_repeats() {
+    // 5-digit IDs can only be a single digit repeated
+    //
+    let idr = IdRange::new(10000, 99999);
+    let mut actual = idr.invalid_ids();
+    actual.sort();
+    let expected: Vec<u64> = vec![
+        11111, 22222, 33333, 44444, 55555, 66666, 77777, 88888, 99999,
+    ];
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn sum_with_and_without_trailing_comma() {
+    let expected: u64 = 11 + 22 + 99 + 111 + 999 + 1010;
+    for raw_input in [
+        "11-22,95-115,998-1012",
+        "11-22,95-115,998-1012\n",
+        "11-22,95-115,998-1012,",
+        "11-22,95-115,998-1012,\n",
+    ] {
+        assert_eq!(expected, sum_invalid_ids(raw_input.chars()));
+    }
+}
+
+#[test]
+fn flush_token_skips_trailing_comma() {
+    let buffer = |s: &str| s.chars().collect::<Vec<char>>();
+    assert_eq!(None, flush_token(&buffer(",")));
+    assert_eq!(None, flush_token(&buffer(",\n")));
+    assert_eq!(None, flush_token(&buffer("\n")));
+    assert_eq!(None, flush_token(&buffer(" \t\r\n")));
+    assert_eq!(None, flush_token(&buffer("")));
+    assert_eq!(
+        Some("998-1012".to_string()),
+        flush_token(&buffer("998-1012"))
+    );
+    assert_eq!(
+        Some("95-115,".to_string()),
+        flush_token(&buffer("95-115,"))
+    );
+}
+
+#[test]
+fn count_matches_invalid_ids() {
+    let ranges = [
+        (11, 22),
+        (95, 115),
+        (998, 1012),
+        (1188511880, 1188511890),
+        (222220, 222224),
+        (1698522, 1698528),
+        (446443, 446449),
+        (38593856, 38593862),
+        (1, 1000000),
+        (123, 123),
+        (100000, 999999),
+        (1, 10000000000),
+    ];
+    for (start, end) in ranges {
+        let idr = IdRange::new(start, end);
+        assert_eq!(
+            idr.invalid_ids().len() as u64,
+            idr.count_invalid_ids(),
+            "{}-{}",
+            start,
+            end
+        );
+    }
+}
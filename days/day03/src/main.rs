@@ -1,10 +1,12 @@
 use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
 // use utf8_chars::BufReadCharsExt;
 
 /// Given input file containing the battery bank specs,
@@ -13,10 +15,45 @@ use clap::Parser;
 ///
 #[derive(Parser)]
 struct Cli {
+    /// Prefer the latest of equal joltage batteries when selecting
+    #[arg(long = "latest-max")]
+    latest_max: bool,
+    /// The number of batteries to select from each bank, unless
+    /// the bank spec has a `count:` prefix; default 12
+    #[arg(short = 'b', long = "battery-count")]
+    battery_count: Option<u32>,
+    /// What to report: the total joltage of all banks, the largest
+    /// single bank joltage, or both
+    #[arg(long = "report", value_enum, default_value_t = ReportMode::Sum)]
+    report: ReportMode,
+    /// Find the max joltages of the banks in parallel
+    #[arg(long = "parallel")]
+    parallel: bool,
     /// The path to the file containing battery bank specs
     path: PathBuf,
 }
 
+// How to choose among batteries with equal joltage when selecting
+// the largest battery within a window of candidates.
+//
+// EarliestMax yields the puzzle answer, since choosing the earliest
+// battery leaves the most candidates for the remaining selections.
+//
+#[derive(Debug, Clone, Copy)]
+enum SelectionStrategy {
+    EarliestMax,
+    LatestMax,
+}
+
+// Which joltage figures to report
+//
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum ReportMode {
+    Sum,
+    Max,
+    Both,
+}
+
 // Elevator battery bank info.
 //
 struct BatteryBank {
@@ -57,7 +94,25 @@ impl BatteryBank {
         let mut j_max: u64 = 0;
         for i in idx_from..idx_to {
             let j: u64 = *jbi.get(&i).unwrap();
-            if j > j_max {
+            if (idx == u32::MAX) || (j > j_max) {
+                j_max = j;
+                idx = i;
+            }
+        }
+        if idx == u32::MAX { None } else { Some(idx) }
+    }
+
+    fn find_last_largest(
+        &self,
+        idx_from: u32,
+        idx_to: u32,
+    ) -> Option<u32> {
+        let jbi = &self.joltage_by_idx;
+        let mut idx: u32 = u32::MAX;
+        let mut j_max: u64 = 0;
+        for i in (idx_from..idx_to).rev() {
+            let j: u64 = *jbi.get(&i).unwrap();
+            if (idx == u32::MAX) || (j > j_max) {
                 j_max = j;
                 idx = i;
             }
@@ -65,7 +120,36 @@ impl BatteryBank {
         if idx == u32::MAX { None } else { Some(idx) }
     }
 
-    fn max_joltage(&self, battery_count: u32) -> Option<u64> {
+    fn max_joltage(
+        &self,
+        battery_count: u32,
+        strategy: SelectionStrategy,
+    ) -> Option<u64> {
+        let jbi = &self.joltage_by_idx;
+        let batteries =
+            self.max_joltage_indices(battery_count, strategy)?;
+        //
+        // construct the joltage of the selected batteries
+        //
+        let mut selected_joltage: u64 = 0;
+        for idx in batteries {
+            selected_joltage =
+                selected_joltage * 10 + *jbi.get(&idx).unwrap();
+        }
+        Some(selected_joltage)
+    }
+
+    // The ids of the batteries selected for the largest joltage,
+    // in bank order, i.e. most significant digit first.
+    //
+    // Returns None if the bank has fewer than battery_count
+    // batteries.
+    //
+    fn max_joltage_indices(
+        &self,
+        battery_count: u32,
+        strategy: SelectionStrategy,
+    ) -> Option<Vec<u32>> {
         let jbi = &self.joltage_by_idx;
         let jbi_len: u32 = jbi.len().try_into().unwrap();
         // if there are fewer batteries in the bank than requested
@@ -85,7 +169,15 @@ impl BatteryBank {
         let mut idx_start: u32 = 0;
         let mut idx_up_to: u32 = jbi_len - remaining_battery_count + 1;
         for _battery in 0..battery_count {
-            match self.find_first_largest(idx_start, idx_up_to) {
+            let found = match strategy {
+                SelectionStrategy::EarliestMax => {
+                    self.find_first_largest(idx_start, idx_up_to)
+                }
+                SelectionStrategy::LatestMax => {
+                    self.find_last_largest(idx_start, idx_up_to)
+                }
+            };
+            match found {
                 None => return None,
                 Some(idx) => {
                     batteries.push(idx);
@@ -95,16 +187,225 @@ impl BatteryBank {
                 }
             }
         }
+        Some(batteries)
+    }
+
+    // The digits of the batteries selected for the largest
+    // joltage, as written, e.g. "98" for "1234567898765432".
+    //
+    // Returns None if the bank has fewer than battery_count
+    // batteries.
+    //
+    #[allow(dead_code)]
+    fn max_joltage_digits(&self, battery_count: u32) -> Option<String> {
+        let batteries = self.max_joltage_indices(
+            battery_count,
+            SelectionStrategy::EarliestMax,
+        )?;
+        let digits: String = batteries
+            .iter()
+            .map(|idx| {
+                let j: u32 = (*self.joltage_by_idx.get(idx).unwrap())
+                    .try_into()
+                    .unwrap();
+                char::from_digit(j, 10).unwrap()
+            })
+            .collect();
+        Some(digits)
+    }
+
+    fn find_first_smallest(
+        &self,
+        idx_from: u32,
+        idx_to: u32,
+    ) -> Option<u32> {
+        let jbi = &self.joltage_by_idx;
+        let mut idx: u32 = u32::MAX;
+        let mut j_min: u64 = u64::MAX;
+        for i in idx_from..idx_to {
+            let j: u64 = *jbi.get(&i).unwrap();
+            if (idx == u32::MAX) || (j < j_min) {
+                j_min = j;
+                idx = i;
+            }
+        }
+        if idx == u32::MAX { None } else { Some(idx) }
+    }
+
+    // The smallest joltage that can be made by selecting
+    // battery_count batteries, the counterpart of max_joltage.
+    //
+    fn min_joltage(&self, battery_count: u32) -> Option<u64> {
+        let jbi = &self.joltage_by_idx;
+        let jbi_len: u32 = jbi.len().try_into().unwrap();
+        if battery_count > jbi_len {
+            return None;
+        }
         //
-        // Now construct the joltage of the selected batteries
+        // loop through the range of batteries that can be
+        // considered for each unidentified battery, identifying
+        // the first battery with the smallest joltage.
         //
         let mut selected_joltage: u64 = 0;
-        for idx in batteries {
+        let mut remaining_battery_count: u32 = battery_count;
+        let mut idx_start: u32 = 0;
+        for _battery in 0..battery_count {
+            let idx_up_to: u32 = jbi_len - remaining_battery_count + 1;
+            let idx = self.find_first_smallest(idx_start, idx_up_to)?;
             selected_joltage =
                 selected_joltage * 10 + *jbi.get(&idx).unwrap();
+            remaining_battery_count -= 1;
+            idx_start = idx + 1;
         }
         Some(selected_joltage)
     }
+
+    // The largest and smallest joltages that can be made by
+    // selecting battery_count batteries, and their difference.
+    //
+    // Returns None if the bank has fewer than battery_count
+    // batteries; otherwise Some((max, min, max - min)).
+    //
+    #[allow(dead_code)]
+    fn joltage_spread(
+        &self,
+        battery_count: u32,
+    ) -> Option<(u64, u64, u64)> {
+        let max = self.max_joltage(
+            battery_count,
+            SelectionStrategy::EarliestMax,
+        )?;
+        let min = self.min_joltage(battery_count)?;
+        Some((max, min, max - min))
+    }
+}
+
+// Why a bank spec line cannot be used
+//
+#[derive(Debug, PartialEq)]
+enum BankLineError {
+    // the (one-based) line number and the `count:` prefix that is
+    // not a number
+    BadCount(usize, String),
+    // the (one-based) line number, the number of batteries to
+    // select, and the number of batteries in the bank
+    TooFewBatteries(usize, u32, u32),
+}
+
+impl fmt::Display for BankLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankLineError::BadCount(line_no, count) => {
+                write!(
+                    f,
+                    "Line {}: the battery count `{}` is not a number",
+                    line_no, count
+                )
+            }
+            BankLineError::TooFewBatteries(line_no, count, len) => {
+                write!(
+                    f,
+                    "Line {}: cannot select {} batteries from a bank of {}",
+                    line_no, count, len
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BankLineError {}
+
+// Split a bank spec line into the number of batteries to select
+// and the battery joltages. The count comes from an optional
+// `count:` prefix (e.g. `3:912834`); otherwise default_count.
+//
+// Returns an error naming the (one-based) line number if the
+// prefix is not a number.
+//
+fn parse_bank_line(
+    line_no: usize,
+    line: &str,
+    default_count: u32,
+) -> Result<(u32, &str), BankLineError> {
+    match line.split_once(':') {
+        Some((count, spec)) => match count.trim().parse::<u32>() {
+            Ok(count) => Ok((count, spec.trim())),
+            Err(_) => Err(BankLineError::BadCount(
+                line_no,
+                count.trim().to_string(),
+            )),
+        },
+        None => Ok((default_count, line)),
+    }
+}
+
+// Find the max joltage of each bank, one bank spec per line, in
+// the order of the lines. In parallel, the banks are spread across
+// threads; the joltages come back in the same order.
+//
+// Each line gives its joltage, or an error naming the line if its
+// count prefix is not a number or its bank has fewer batteries
+// than are to be selected.
+//
+fn max_joltages(
+    lines: &[String],
+    default_count: u32,
+    strategy: SelectionStrategy,
+    parallel: bool,
+) -> Vec<Result<u64, BankLineError>> {
+    let max_joltage =
+        |(idx, line): (usize, &String)| -> Result<u64, BankLineError> {
+            let line_no = idx + 1;
+            let (count, spec) =
+                parse_bank_line(line_no, line.trim(), default_count)?;
+            let bank = BatteryBank::new(spec);
+            bank.max_joltage(count, strategy).ok_or_else(|| {
+                BankLineError::TooFewBatteries(
+                    line_no,
+                    count,
+                    bank.joltage_by_idx.len().try_into().unwrap(),
+                )
+            })
+        };
+    if parallel {
+        lines.par_iter().enumerate().map(max_joltage).collect()
+    } else {
+        lines.iter().enumerate().map(max_joltage).collect()
+    }
+}
+
+// Fold the bank joltages into their sum and the largest one.
+//
+// The sum is None if it overflows.
+//
+fn sum_and_max(joltages: &[u64]) -> (Option<u64>, u64) {
+    joltages.iter().fold((Some(0), 0), |(sum, max), j| {
+        (sum.and_then(|s| s.checked_add(*j)), u64::max(max, *j))
+    })
+}
+
+// Produce the report lines for the bank joltages
+//
+fn report(joltages: &[u64], mode: ReportMode) -> Vec<String> {
+    let (sum, max) = sum_and_max(joltages);
+    let mut lines: Vec<String> = Vec::new();
+    if ReportMode::Max != mode {
+        match sum {
+            Some(sum) => {
+                lines.push(format!("The total joltage is {}.", sum));
+            }
+            None => {
+                lines.push(
+                    "The total joltage is too large for a 64-bit integer."
+                        .to_string(),
+                );
+            }
+        }
+    }
+    if ReportMode::Sum != mode {
+        lines.push(format!("The largest bank joltage is {}.", max));
+    }
+    lines
 }
 
 // Binary crate entry point
@@ -112,33 +413,48 @@ impl BatteryBank {
 fn main() -> Result<()> {
     let args = Cli::parse();
     let path = &args.path;
+    let mut battery_count: u32 = 12;
+    if let Some(x) = args.battery_count {
+        battery_count = x;
+    }
+    let strategy = if args.latest_max {
+        SelectionStrategy::LatestMax
+    } else {
+        SelectionStrategy::EarliestMax
+    };
 
     let f = File::open(path).with_context(|| {
         format!("Could not open `{}`", path.display())
     })?;
     let rdr = BufReader::new(f);
-    let lines = rdr.lines();
+    let mut lines: Vec<String> = Vec::new();
+    for line in rdr.lines() {
+        let line = line.with_context(|| {
+            format!("Problem reading from `{}`", path.display())
+        })?;
+        lines.push(line);
+    }
 
     // determine the max joltage for each bank
     //
+    // report and skip the lines that cannot be used
+    //
     let mut joltages: Vec<u64> = Vec::new();
-    for line in lines {
-        let line = line.with_context(|| {
-            format!("Problem reading from `{}`", path.display())
-        })?;
-        let battery_bank = BatteryBank::new(line.trim());
-        let max_joltage = battery_bank.max_joltage(12).unwrap();
-        joltages.push(max_joltage);
-        // println!("For '{}' max is {}", line.trim(), max_joltage);
+    for joltage in
+        max_joltages(&lines, battery_count, strategy, args.parallel)
+    {
+        match joltage {
+            Ok(j) => joltages.push(j),
+            Err(e) => eprintln!("warning: skipping {}", e),
+        }
     }
 
-    // add up the max joltage for each bank
+    // add up the max joltage for each bank, and/or find the
+    // largest
     //
-    let mut joltage_accum: u64 = 0;
-    for joltage in joltages {
-        joltage_accum += joltage;
+    for line in report(&joltages, args.report) {
+        println!("{}", line);
     }
-    println!("The total joltage is {}.", joltage_accum);
     Ok(())
 }
 
@@ -151,7 +467,7 @@ fn check_all_ones() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 11;
-    match bb.max_joltage(2) {
+    match bb.max_joltage(2, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -165,7 +481,7 @@ fn check_ascending() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 99;
-    match bb.max_joltage(2) {
+    match bb.max_joltage(2, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -179,7 +495,7 @@ fn check_ascending_descending() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 98;
-    match bb.max_joltage(2) {
+    match bb.max_joltage(2, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -193,7 +509,7 @@ fn check_descending() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 99;
-    match bb.max_joltage(2) {
+    match bb.max_joltage(2, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -207,7 +523,7 @@ fn check_last_biggest() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 89;
-    match bb.max_joltage(2) {
+    match bb.max_joltage(2, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -224,7 +540,7 @@ fn check_all_ones_12() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 111111111111;
-    match bb.max_joltage(12) {
+    match bb.max_joltage(12, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -238,7 +554,7 @@ fn check_ascending_12() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 567899999999;
-    match bb.max_joltage(12) {
+    match bb.max_joltage(12, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -252,7 +568,7 @@ fn check_ascending_descending_12() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 567898765432;
-    match bb.max_joltage(12) {
+    match bb.max_joltage(12, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -266,7 +582,7 @@ fn check_descending_12() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 987659876543;
-    match bb.max_joltage(12) {
+    match bb.max_joltage(12, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
@@ -280,10 +596,211 @@ fn check_last_biggest_12() {
     let s: &str = ss.as_str();
     let bb = BatteryBank::new(s);
     let expected: u64 = 888765432119;
-    match bb.max_joltage(12) {
+    match bb.max_joltage(12, SelectionStrategy::EarliestMax) {
         None => {
             assert!(false, "FAILED to find max joltage")
         }
         Some(actual) => assert_eq!(expected, actual),
     }
 }
+
+// BatteryBank tests with each selection strategy
+//
+
+#[test]
+fn check_strategies_diverge() {
+    let bb = BatteryBank::new("9090");
+    let earliest = bb.max_joltage(2, SelectionStrategy::EarliestMax);
+    let latest = bb.max_joltage(2, SelectionStrategy::LatestMax);
+    assert_eq!(Some(99), earliest);
+    assert_eq!(Some(90), latest);
+}
+
+#[test]
+fn check_strategies_diverge_longer() {
+    let bb = BatteryBank::new("909090");
+    let earliest = bb.max_joltage(3, SelectionStrategy::EarliestMax);
+    let latest = bb.max_joltage(3, SelectionStrategy::LatestMax);
+    assert_eq!(Some(999), earliest);
+    assert_eq!(Some(990), latest);
+}
+
+#[test]
+fn check_strategies_agree_without_ties() {
+    let bb = BatteryBank::new("1234567898765432");
+    let earliest = bb.max_joltage(2, SelectionStrategy::EarliestMax);
+    let latest = bb.max_joltage(2, SelectionStrategy::LatestMax);
+    assert_eq!(Some(98), earliest);
+    assert_eq!(earliest, latest);
+}
+
+#[test]
+fn check_joltage_spread() {
+    let bb = BatteryBank::new("1234567898765432");
+    assert_eq!(Some(12), bb.min_joltage(2));
+    assert_eq!(Some((98, 12, 86)), bb.joltage_spread(2));
+    assert_eq!(
+        Some((98765432, 12345432, 86420000)),
+        bb.joltage_spread(8)
+    );
+    assert_eq!(None, bb.joltage_spread(17));
+}
+
+#[test]
+fn check_max_joltage_digits() {
+    let bb = BatteryBank::new("8181568765432119");
+    assert_eq!(
+        Some("888765432119".to_string()),
+        bb.max_joltage_digits(12)
+    );
+    assert_eq!(
+        Some(888765432119),
+        bb.max_joltage(12, SelectionStrategy::EarliestMax)
+    );
+    assert_eq!(Some("89".to_string()), bb.max_joltage_digits(2));
+    assert_eq!(None, bb.max_joltage_digits(17));
+
+    // leading zeros are kept in the digits
+    //
+    let bb = BatteryBank::new("0012");
+    assert_eq!(Some("0012".to_string()), bb.max_joltage_digits(4));
+    assert_eq!(
+        Some(12),
+        bb.max_joltage(4, SelectionStrategy::EarliestMax)
+    );
+}
+
+// bank spec line tests
+//
+
+#[test]
+fn check_line_with_count_prefix() {
+    let (count, spec) = parse_bank_line(1, "3:912834", 12).unwrap();
+    assert_eq!(3, count);
+    assert_eq!("912834", spec);
+    let bb = BatteryBank::new(spec);
+    let actual = bb.max_joltage(count, SelectionStrategy::EarliestMax);
+    assert_eq!(Some(984), actual);
+}
+
+#[test]
+fn check_line_without_count_prefix() {
+    let (count, spec) = parse_bank_line(1, "912834", 2).unwrap();
+    assert_eq!(2, count);
+    assert_eq!("912834", spec);
+    let bb = BatteryBank::new(spec);
+    let actual = bb.max_joltage(count, SelectionStrategy::EarliestMax);
+    assert_eq!(Some(98), actual);
+}
+
+#[test]
+fn check_bad_lines() {
+    assert_eq!(
+        Err(BankLineError::BadCount(4, "x".to_string())),
+        parse_bank_line(4, "x:123", 2)
+    );
+    let lines: Vec<String> = ["912834", "x:123", "7:912834", ""]
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    assert_eq!(
+        vec![
+            Ok(98),
+            Err(BankLineError::BadCount(2, "x".to_string())),
+            Err(BankLineError::TooFewBatteries(3, 7, 6)),
+            Err(BankLineError::TooFewBatteries(4, 2, 0)),
+        ],
+        max_joltages(&lines, 2, SelectionStrategy::EarliestMax, false)
+    );
+    assert_eq!(
+        "Line 2: the battery count `x` is not a number",
+        BankLineError::BadCount(2, "x".to_string()).to_string()
+    );
+}
+
+// report tests
+//
+
+#[cfg(test)]
+fn testhelper_sample_joltages() -> Vec<u64> {
+    let specs = [
+        "987654321111111",
+        "811111111111119",
+        "234234234234278",
+        "818181911112111",
+    ];
+    specs
+        .iter()
+        .map(|spec| {
+            BatteryBank::new(spec)
+                .max_joltage(2, SelectionStrategy::EarliestMax)
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn check_parallel_matches_serial() {
+    let lines: Vec<String> = [
+        "987654321111111",
+        "811111111111119",
+        "234234234234278",
+        "818181911112111",
+        "2:818181911112111",
+    ]
+    .iter()
+    .map(|x| x.to_string())
+    .collect();
+    let strategy = SelectionStrategy::EarliestMax;
+    let serial: Vec<u64> = max_joltages(&lines, 12, strategy, false)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let parallel: Vec<u64> = max_joltages(&lines, 12, strategy, true)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(serial, parallel);
+    assert_eq!(Some(3121910778619 + 92), sum_and_max(&parallel).0);
+    assert_eq!(sum_and_max(&serial), sum_and_max(&parallel));
+}
+
+#[test]
+fn check_total_overflow() {
+    let joltages: Vec<u64> = vec![u64::MAX, 1];
+    assert_eq!((None, u64::MAX), sum_and_max(&joltages));
+    assert_eq!(
+        vec!["The total joltage is too large for a 64-bit integer."],
+        report(&joltages, ReportMode::Sum)
+    );
+}
+
+#[test]
+fn check_report_sum() {
+    let joltages = testhelper_sample_joltages();
+    assert_eq!(
+        vec!["The total joltage is 357."],
+        report(&joltages, ReportMode::Sum)
+    );
+}
+
+#[test]
+fn check_report_max() {
+    let joltages = testhelper_sample_joltages();
+    assert_eq!(
+        vec!["The largest bank joltage is 98."],
+        report(&joltages, ReportMode::Max)
+    );
+}
+
+#[test]
+fn check_report_both() {
+    let joltages = testhelper_sample_joltages();
+    assert_eq!(
+        vec![
+            "The total joltage is 357.",
+            "The largest bank joltage is 98."
+        ],
+        report(&joltages, ReportMode::Both)
+    );
+}
@@ -5,7 +5,7 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 /// Given input file containing the paper roll grid,
 /// output the number of paper rolls accessible by a forklift.
@@ -15,6 +15,16 @@ use clap::Parser;
 ///
 #[derive(Parser)]
 struct Cli {
+    /// Treat the cells beyond the edge of the grid as rolls
+    #[arg(long = "border-rolls")]
+    border_rolls: bool,
+    /// Which neighboring cells count toward a roll's neighbors
+    #[arg(long = "neighbors", value_enum, default_value = "all")]
+    neighbors: NeighborMode,
+    /// The first line of the file is a `rows cols` header giving
+    /// the dimensions of the grid
+    #[arg(long = "header")]
+    header: bool,
     /// The path to the file containing battery bank specs
     path: PathBuf,
 }
@@ -23,6 +33,10 @@ struct Cli {
 enum PaperRollGridError {
     InputRowWrongLength,
     InvalidInputCharacter,
+    InvalidHeader,
+    WrongRowCount,
+    CellOutOfBounds,
+    InvalidBitset,
 }
 
 impl fmt::Display for PaperRollGridError {
@@ -37,19 +51,88 @@ impl fmt::Display for PaperRollGridError {
             PaperRollGridError::InvalidInputCharacter => {
                 write!(f, "Invalid grid specification character")
             }
+            PaperRollGridError::InvalidHeader => {
+                write!(
+                    f,
+                    "Grid header must be the row and column counts, e.g. `10 10`"
+                )
+            }
+            PaperRollGridError::WrongRowCount => {
+                write!(
+                    f,
+                    "Number of rows does not match the grid header"
+                )
+            }
+            PaperRollGridError::CellOutOfBounds => {
+                write!(f, "Cell coordinates are not within the grid")
+            }
+            PaperRollGridError::InvalidBitset => {
+                write!(
+                    f,
+                    "Bitset does not match the grid dimensions it declares"
+                )
+            }
         }
     }
 }
 
 impl std::error::Error for PaperRollGridError {}
 
+// What to assume about the cells beyond the edge of the grid
+// when counting neighboring rolls
+//
+#[derive(Debug, Clone, Copy)]
+enum BorderPolicy {
+    Empty,
+    Roll,
+}
+
+// Which of the eight surrounding cells count as neighbors
+//
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum NeighborMode {
+    // all eight surrounding cells
+    All,
+    // the four cells above, below, left and right
+    Orthogonal,
+    // the four cells touching only at a corner
+    Diagonal,
+}
+
+impl NeighborMode {
+    // Returns true if a neighbor in that direction counts
+    //
+    fn includes(&self, diagonal: bool) -> bool {
+        match self {
+            NeighborMode::All => true,
+            NeighborMode::Orthogonal => !diagonal,
+            NeighborMode::Diagonal => diagonal,
+        }
+    }
+}
+
 // The coordinates of a cell in the grid
 //
+#[derive(Debug, PartialEq)]
 struct GridCell {
     row_idx: u32,
     col_idx: u32,
 }
 
+// Everything about the grid's accessibility for one neighbor mode
+// and threshold, gathered in a single pass
+//
+#[allow(dead_code)]
+struct AccessibilityReport {
+    // the number of rolls with fewer than threshold neighbors
+    accessible_count: u32,
+    // those rolls, in row then column order
+    accessible_cells: Vec<GridCell>,
+    // the neighboring roll count of every cell, indexed by row
+    // then column, whether or not the cell holds a roll
+    neighbor_counts: Vec<Vec<u32>>,
+}
+
 struct PaperRollGrid {
     // A collection of rows indexed by zero-based row number.
     // Each row is a collection of cells indexed by zero-based
@@ -59,6 +142,11 @@ struct PaperRollGrid {
     rows: BTreeMap<u32, BTreeMap<u32, bool>>,
     row_count: u32,
     col_count: u32,
+    border: BorderPolicy,
+    neighbor_mode: NeighborMode,
+    // The (row_count, col_count) declared by a header line, if any
+    //
+    declared_dims: Option<(u32, u32)>,
 }
 
 impl PaperRollGrid {
@@ -70,6 +158,176 @@ impl PaperRollGrid {
             rows: g,
             row_count: 0,
             col_count: 0,
+            border: BorderPolicy::Empty,
+            neighbor_mode: NeighborMode::All,
+            declared_dims: None,
+        }
+    }
+
+    // constructor for a grid whose dimensions are given by a
+    // `rows cols` header line; the rows added must match it.
+    //
+    fn new_from_header(
+        header: &str,
+    ) -> Result<Self, PaperRollGridError> {
+        let dims: Vec<&str> = header.split_whitespace().collect();
+        if 2 != dims.len() {
+            return Err(PaperRollGridError::InvalidHeader);
+        }
+        let row_count: u32 = dims[0]
+            .parse()
+            .map_err(|_| PaperRollGridError::InvalidHeader)?;
+        let col_count: u32 = dims[1]
+            .parse()
+            .map_err(|_| PaperRollGridError::InvalidHeader)?;
+        let mut grid = PaperRollGrid::new();
+        grid.declared_dims = Some((row_count, col_count));
+        Ok(grid)
+    }
+
+    // constructor for a grid packed by to_bits. The border policy
+    // and neighbor mode are not packed, so are the defaults.
+    //
+    #[allow(dead_code)]
+    fn from_bits(bits: &[u64]) -> Result<Self, PaperRollGridError> {
+        if 2 > bits.len() {
+            return Err(PaperRollGridError::InvalidBitset);
+        }
+        let row_count: u32 = bits[0]
+            .try_into()
+            .map_err(|_| PaperRollGridError::InvalidBitset)?;
+        let col_count: u32 = bits[1]
+            .try_into()
+            .map_err(|_| PaperRollGridError::InvalidBitset)?;
+        let cell_count = (row_count as usize) * (col_count as usize);
+        if (2 + cell_count.div_ceil(64)) != bits.len() {
+            return Err(PaperRollGridError::InvalidBitset);
+        }
+        let mut grid = PaperRollGrid::new();
+        for ridx in 0..row_count {
+            let mut row: BTreeMap<u32, bool> = BTreeMap::new();
+            for cidx in 0..col_count {
+                let i = (ridx as usize) * (col_count as usize)
+                    + (cidx as usize);
+                let contains_roll =
+                    0 != (bits[2 + i / 64] >> (i % 64)) & 1;
+                row.insert(cidx, contains_roll);
+            }
+            grid.rows.insert(ridx, row);
+        }
+        grid.row_count = row_count;
+        grid.col_count = col_count;
+        Ok(grid)
+    }
+
+    // Pack the grid into a bitset: the row count, then the column
+    // count, then one bit per cell, set if it holds a roll, in row
+    // then column order from the low bit of each word up.
+    //
+    #[allow(dead_code)]
+    fn to_bits(&self) -> Vec<u64> {
+        let cell_count =
+            (self.row_count as usize) * (self.col_count as usize);
+        let mut bits: Vec<u64> = vec![0; 2 + cell_count.div_ceil(64)];
+        bits[0] = self.row_count as u64;
+        bits[1] = self.col_count as u64;
+        for ridx in 0..self.row_count {
+            for cidx in 0..self.col_count {
+                if self.has_roll(&ridx, &cidx) {
+                    let i = (ridx as usize) * (self.col_count as usize)
+                        + (cidx as usize);
+                    bits[2 + i / 64] |= 1 << (i % 64);
+                }
+            }
+        }
+        bits
+    }
+
+    // The (row, column) of every cell holding a roll, in row then
+    // column order
+    //
+    #[allow(dead_code)]
+    fn roll_coords(&self) -> Vec<(u32, u32)> {
+        let mut coords: Vec<(u32, u32)> = Vec::new();
+        for ridx in 0..self.row_count {
+            for cidx in 0..self.col_count {
+                if self.has_roll(&ridx, &cidx) {
+                    coords.push((ridx, cidx));
+                }
+            }
+        }
+        coords
+    }
+
+    // Check that the grid has all the rows declared by its
+    // header. A grid without a header always passes.
+    //
+    fn check_declared_rows(&self) -> Result<(), PaperRollGridError> {
+        match self.declared_dims {
+            Some((row_count, _)) if row_count != self.row_count => {
+                Err(PaperRollGridError::WrongRowCount)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Set what to assume about the cells beyond the edge of
+    // the grid
+    //
+    fn set_border_policy(&mut self, border: BorderPolicy) {
+        self.border = border;
+    }
+
+    // Set which surrounding cells count as neighbors
+    //
+    fn set_neighbor_mode(&mut self, mode: NeighborMode) {
+        self.neighbor_mode = mode;
+    }
+
+    // For the cell at (row_idx, col_idx), count the neighboring
+    // cells, under the given neighbor mode, that are beyond the
+    // edge of the grid.
+    //
+    fn count_out_of_grid_neighbors(
+        &self,
+        row_idx: u32,
+        col_idx: u32,
+        mode: NeighborMode,
+    ) -> u32 {
+        // the orthogonal neighbors beyond each edge the cell is on
+        //
+        let mut orthogonal_out: u32 = 0;
+        if row_idx == 0 {
+            orthogonal_out += 1;
+        }
+        if row_idx == (self.row_count - 1) {
+            orthogonal_out += 1;
+        }
+        if col_idx == 0 {
+            orthogonal_out += 1;
+        }
+        if col_idx == (self.col_count - 1) {
+            orthogonal_out += 1;
+        }
+        let mut rows_in: u32 = 3;
+        if row_idx == 0 {
+            rows_in -= 1;
+        }
+        if row_idx == (self.row_count - 1) {
+            rows_in -= 1;
+        }
+        let mut cols_in: u32 = 3;
+        if col_idx == 0 {
+            cols_in -= 1;
+        }
+        if col_idx == (self.col_count - 1) {
+            cols_in -= 1;
+        }
+        let all_out: u32 = 8 - ((rows_in * cols_in) - 1);
+        match mode {
+            NeighborMode::All => all_out,
+            NeighborMode::Orthogonal => orthogonal_out,
+            NeighborMode::Diagonal => all_out - orthogonal_out,
         }
     }
 
@@ -81,6 +339,15 @@ impl PaperRollGrid {
         row_spec: &str,
     ) -> Result<u32, PaperRollGridError> {
         let mut roll_count: u32 = 0;
+        if let Some((row_count, col_count)) = self.declared_dims {
+            if self.row_count >= row_count {
+                return Err(PaperRollGridError::WrongRowCount);
+            }
+            let rl: u32 = row_spec.chars().count().try_into().unwrap();
+            if col_count != rl {
+                return Err(PaperRollGridError::InputRowWrongLength);
+            }
+        }
         if self.rows.len() == 0 {
             let mut row: BTreeMap<u32, bool> = BTreeMap::new();
             for (ii, c) in row_spec.chars().enumerate() {
@@ -135,12 +402,29 @@ impl PaperRollGrid {
     //
     // Returns None if cell is not within the grid; returns
     // Some(count) where count is the number of neighboring cells
-    // containing a roll of paper.
+    // containing a roll of paper. Under BorderPolicy::Roll the
+    // neighbors beyond the edge of the grid count as rolls.
     //
     fn count_neighboring_rolls(
         &self,
         row_idx: u32,
         col_idx: u32,
+    ) -> Option<u32> {
+        self.count_neighboring_rolls_in_mode(
+            row_idx,
+            col_idx,
+            self.neighbor_mode,
+        )
+    }
+
+    // As count_neighboring_rolls, but counting the neighbors
+    // given by mode rather than the grid's neighbor mode
+    //
+    fn count_neighboring_rolls_in_mode(
+        &self,
+        row_idx: u32,
+        col_idx: u32,
+        mode: NeighborMode,
     ) -> Option<u32> {
         // check whether cell is within the grid
         //
@@ -161,7 +445,9 @@ impl PaperRollGrid {
                 col_idx + 1
             };
             for cidx in cidx_from..=cidx_to {
-                if self.has_roll(&ridx, &cidx) {
+                if mode.includes(cidx != col_idx)
+                    && self.has_roll(&ridx, &cidx)
+                {
                     roll_count += 1;
                 }
             }
@@ -170,13 +456,14 @@ impl PaperRollGrid {
         // look at neighbors on each side
         //
         let ridx: u32 = row_idx;
-        if col_idx > 0 {
+        let sides = mode.includes(false);
+        if sides && col_idx > 0 {
             let cidx: u32 = col_idx - 1;
             if self.has_roll(&ridx, &cidx) {
                 roll_count += 1;
             }
         }
-        if col_idx < (self.col_count - 1) {
+        if sides && col_idx < (self.col_count - 1) {
             let cidx: u32 = col_idx + 1;
             if self.has_roll(&ridx, &cidx) {
                 roll_count += 1;
@@ -195,17 +482,116 @@ impl PaperRollGrid {
                 col_idx + 1
             };
             for cidx in cidx_from..=cidx_to {
-                if self.has_roll(&ridx, &cidx) {
+                if mode.includes(cidx != col_idx)
+                    && self.has_roll(&ridx, &cidx)
+                {
                     roll_count += 1;
                 }
             }
         }
         //
+        // account for the border
+        //
+        if let BorderPolicy::Roll = self.border {
+            roll_count += self
+                .count_out_of_grid_neighbors(row_idx, col_idx, mode);
+        }
+        //
         // Get out
         //
         Some(roll_count)
     }
 
+    // Whether the roll at (row_idx, col_idx) can be accessed by
+    // a forklift, i.e. has fewer than threshold neighboring rolls.
+    //
+    // Returns None if the cell is not within the grid or does not
+    // contain a roll.
+    //
+    fn is_roll_accessible(
+        &self,
+        row_idx: u32,
+        col_idx: u32,
+        threshold: u32,
+    ) -> Option<bool> {
+        if (row_idx >= self.row_count) || (col_idx >= self.col_count) {
+            return None;
+        }
+        if !self.has_roll(&row_idx, &col_idx) {
+            return None;
+        }
+        let roll_count =
+            self.count_neighboring_rolls(row_idx, col_idx)?;
+        Some(roll_count < threshold)
+    }
+
+    // The number of rolls accessible by forklift for every
+    // threshold from 0 through 8, i.e. index t is the number of
+    // rolls with fewer than t neighboring rolls.
+    //
+    #[allow(dead_code)]
+    fn accessibility_curve(&self) -> [u32; 9] {
+        // bucket the rolls by their neighbor count
+        //
+        let mut by_count: [u32; 9] = [0; 9];
+        for ridx in 0..self.row_count {
+            for cidx in 0..self.col_count {
+                if !self.has_roll(&ridx, &cidx) {
+                    continue;
+                }
+                let roll_count =
+                    self.count_neighboring_rolls(ridx, cidx).unwrap();
+                by_count[roll_count as usize] += 1;
+            }
+        }
+        //
+        // accumulate the buckets below each threshold
+        //
+        let mut curve: [u32; 9] = [0; 9];
+        for t in 1..9 {
+            curve[t] = curve[t - 1] + by_count[t - 1];
+        }
+        curve
+    }
+
+    // Count the neighbors of every cell under the given mode, and
+    // find the rolls with fewer than threshold neighboring rolls.
+    //
+    #[allow(dead_code)]
+    fn accessibility_report(
+        &self,
+        mode: NeighborMode,
+        threshold: u32,
+    ) -> AccessibilityReport {
+        let mut accessible_cells: Vec<GridCell> = Vec::new();
+        let mut neighbor_counts: Vec<Vec<u32>> = Vec::new();
+        for ridx in 0..self.row_count {
+            let mut row_counts: Vec<u32> = Vec::new();
+            for cidx in 0..self.col_count {
+                let roll_count = self
+                    .count_neighboring_rolls_in_mode(ridx, cidx, mode)
+                    .unwrap();
+                row_counts.push(roll_count);
+                if self.has_roll(&ridx, &cidx) && roll_count < threshold
+                {
+                    accessible_cells.push(GridCell {
+                        row_idx: ridx,
+                        col_idx: cidx,
+                    });
+                }
+            }
+            neighbor_counts.push(row_counts);
+        }
+        AccessibilityReport {
+            accessible_count: accessible_cells
+                .len()
+                .try_into()
+                .unwrap(),
+            accessible_cells,
+            neighbor_counts,
+        }
+    }
+
     // Get the cell value
     //
     // Will panic if cell coordinates are not within the grid.
@@ -215,6 +601,27 @@ impl PaperRollGrid {
         *row.get(col_idx).unwrap()
     }
 
+    // Place (present is true) or remove (present is false) a roll
+    // in the cell.
+    //
+    // Returns an error if the cell is not within the grid.
+    //
+    #[allow(dead_code)]
+    fn set_roll(
+        &mut self,
+        row_idx: u32,
+        col_idx: u32,
+        present: bool,
+    ) -> Result<(), PaperRollGridError> {
+        if (row_idx >= self.row_count) || (col_idx >= self.col_count) {
+            return Err(PaperRollGridError::CellOutOfBounds);
+        }
+        let row = self.rows.get_mut(&row_idx).unwrap();
+        let grid_cell = row.get_mut(&col_idx).unwrap();
+        *grid_cell = present;
+        Ok(())
+    }
+
     // Set the cell value to false
     //
     // Will panic if cell coordinates are not within the grid.
@@ -226,6 +633,74 @@ impl PaperRollGrid {
             *grid_cell = false;
         }
     }
+
+    // Repeatedly remove the rolls accessible by forklift until
+    // no more can be removed.
+    //
+    // Returns a map of pass number (starting at 1) to the number
+    // of rolls removed during that pass.
+    //
+    fn removable_in_passes(&mut self) -> BTreeMap<u32, u32> {
+        let mut removed_by_pass: BTreeMap<u32, u32> = BTreeMap::new();
+        let mut pass: u32 = 0;
+        loop {
+            let removeable_rolls = self.accessible_rolls(4);
+            //
+            // Remove the accessible rolls
+            //
+            if 0 == removeable_rolls.len() {
+                break;
+            }
+            pass += 1;
+            let removed: u32 =
+                removeable_rolls.len().try_into().unwrap();
+            removed_by_pass.insert(pass, removed);
+            self.remove_rolls(&removeable_rolls);
+        }
+        removed_by_pass
+    }
+
+    // The rolls with fewer than threshold neighboring rolls, in
+    // row then column order
+    //
+    fn accessible_rolls(&self, threshold: u32) -> Vec<GridCell> {
+        let mut rolls: Vec<GridCell> = Vec::new();
+        for ridx in 0..self.row_count {
+            for cidx in 0..self.col_count {
+                if let Some(true) =
+                    self.is_roll_accessible(ridx, cidx, threshold)
+                {
+                    rolls.push(GridCell {
+                        row_idx: ridx,
+                        col_idx: cidx,
+                    });
+                }
+            }
+        }
+        rolls
+    }
+
+    // Run one removal pass per threshold, in order, removing the
+    // rolls accessible under that pass's threshold, e.g. to model
+    // forklifts that get stronger over time.
+    //
+    // Returns the (threshold, rolls removed) of each pass.
+    //
+    #[allow(dead_code)]
+    fn remove_with_thresholds(
+        &mut self,
+        thresholds: &[u32],
+    ) -> Vec<(u32, u32)> {
+        let mut removed_by_pass: Vec<(u32, u32)> = Vec::new();
+        for threshold in thresholds {
+            let removeable_rolls = self.accessible_rolls(*threshold);
+            let removed: u32 =
+                removeable_rolls.len().try_into().unwrap();
+            removed_by_pass.push((*threshold, removed));
+            self.remove_rolls(&removeable_rolls);
+        }
+        removed_by_pass
+    }
 }
 
 // Binary crate entry point
@@ -238,11 +713,24 @@ fn main() -> Result<()> {
         format!("Could not open `{}`", path.display())
     })?;
     let rdr = BufReader::new(f);
-    let lines = rdr.lines();
+    let mut lines = rdr.lines();
 
     // populate the grid
     //
     let mut grid = PaperRollGrid::new();
+    if args.header {
+        let header = match lines.next() {
+            Some(line) => line.with_context(|| {
+                format!("Problem reading from `{}`", path.display())
+            })?,
+            None => String::new(),
+        };
+        grid = PaperRollGrid::new_from_header(&header)?;
+    }
+    if args.border_rolls {
+        grid.set_border_policy(BorderPolicy::Roll);
+    }
+    grid.set_neighbor_mode(args.neighbors);
     for line in lines {
         let line = line.with_context(|| {
             format!("Problem reading from `{}`", path.display())
@@ -250,36 +738,14 @@ fn main() -> Result<()> {
         let line = line.trim();
         _ = grid.add_next_row(line)?;
     }
+    grid.check_declared_rows()?;
     //
     // check the rolls to see if they are removable
     // keep trying as long as removeable rolls remain
     //
     let mut accessible_rolls: u32 = 0;
-    loop {
-        let mut removeable_rolls: Vec<GridCell> = Vec::new();
-        for ridx in 0..grid.row_count {
-            for cidx in 0..grid.col_count {
-                if grid.has_roll(&ridx, &cidx) {
-                    if 4 > grid
-                        .count_neighboring_rolls(ridx, cidx)
-                        .unwrap()
-                    {
-                        removeable_rolls.push(GridCell {
-                            row_idx: ridx,
-                            col_idx: cidx,
-                        });
-                        accessible_rolls += 1;
-                    }
-                }
-            }
-        }
-        //
-        // Remove the accessible rolls
-        //
-        if 0 == removeable_rolls.len() {
-            break;
-        }
-        grid.remove_rolls(&removeable_rolls);
+    for removed in grid.removable_in_passes().values() {
+        accessible_rolls += removed;
     }
 
     // Display the total rolls removed
@@ -324,9 +790,88 @@ fn testhelper_make_grid01() -> PaperRollGrid {
     grid
 }
 
+// The sample grid from the puzzle specs
+//
+// ..@@.@@@@.
+// @@@.@.@.@@
+// @@@@@.@.@@
+// @.@@@@..@.
+// @@.@@@@.@@
+// .@@@@@@@.@
+// .@.@.@.@@@
+// @.@@@.@@@@
+// .@@@@@@@@.
+// @.@.@@@.@.
+//
+#[cfg(test)]
+fn testhelper_make_sample_grid() -> PaperRollGrid {
+    let mut grid = PaperRollGrid::new();
+    let specs = [
+        "..@@.@@@@.",
+        "@@@.@.@.@@",
+        "@@@@@.@.@@",
+        "@.@@@@..@.",
+        "@@.@@@@.@@",
+        ".@@@@@@@.@",
+        ".@.@.@.@@@",
+        "@.@@@.@@@@",
+        ".@@@@@@@@.",
+        "@.@.@@@.@.",
+    ];
+    for spec in specs {
+        let _rolls = grid.add_next_row(spec).unwrap();
+    }
+    grid
+}
+
 // PaperRollGrid tests
 //
 
+#[test]
+fn header_with_matching_grid() {
+    let mut grid = PaperRollGrid::new_from_header("3 4").unwrap();
+    for spec in ["..@@", "@..@", ".@@."] {
+        let _rolls = grid.add_next_row(spec).unwrap();
+    }
+    assert!(grid.check_declared_rows().is_ok());
+    assert_eq!(3, grid.row_count);
+    assert_eq!(4, grid.col_count);
+}
+
+#[test]
+fn header_with_mismatched_grid() {
+    assert!(matches!(
+        PaperRollGrid::new_from_header("3"),
+        Err(PaperRollGridError::InvalidHeader)
+    ));
+
+    // a row with the wrong number of columns
+    //
+    let mut grid = PaperRollGrid::new_from_header("3 4").unwrap();
+    assert!(matches!(
+        grid.add_next_row("..@@."),
+        Err(PaperRollGridError::InputRowWrongLength)
+    ));
+
+    // too few rows
+    //
+    let mut grid = PaperRollGrid::new_from_header("3 4").unwrap();
+    let _rolls = grid.add_next_row("..@@").unwrap();
+    let _rolls = grid.add_next_row("@..@").unwrap();
+    assert!(matches!(
+        grid.check_declared_rows(),
+        Err(PaperRollGridError::WrongRowCount)
+    ));
+
+    // too many rows
+    //
+    let _rolls = grid.add_next_row(".@@.").unwrap();
+    assert!(matches!(
+        grid.add_next_row("@@@@"),
+        Err(PaperRollGridError::WrongRowCount)
+    ));
+}
+
 #[test]
 #[should_panic]
 fn has_invalid_spec_char() {
@@ -379,6 +924,37 @@ fn adding_row_of_different_length() {
     let _rolls = grid.add_next_row(s).unwrap();
 }
 
+#[test]
+fn set_roll_updates_neighbor_counts() {
+    let mut grid: PaperRollGrid = testhelper_make_grid01();
+    assert!(!grid.has_roll(&1, &1));
+    assert_eq!(Some(1), grid.count_neighboring_rolls(0, 0));
+    assert_eq!(Some(3), grid.count_neighboring_rolls(2, 2));
+
+    grid.set_roll(1, 1, true).unwrap();
+    assert!(grid.has_roll(&1, &1));
+    assert_eq!(Some(2), grid.count_neighboring_rolls(0, 0));
+    assert_eq!(Some(4), grid.count_neighboring_rolls(2, 2));
+
+    grid.set_roll(1, 1, false).unwrap();
+    assert!(!grid.has_roll(&1, &1));
+    assert_eq!(Some(1), grid.count_neighboring_rolls(0, 0));
+    assert_eq!(Some(3), grid.count_neighboring_rolls(2, 2));
+}
+
+#[test]
+fn set_roll_out_of_bounds() {
+    let mut grid: PaperRollGrid = testhelper_make_grid01();
+    assert!(matches!(
+        grid.set_roll(6, 0, true),
+        Err(PaperRollGridError::CellOutOfBounds)
+    ));
+    assert!(matches!(
+        grid.set_roll(0, 8, true),
+        Err(PaperRollGridError::CellOutOfBounds)
+    ));
+}
+
 // ..@@...@
 // @..@@...
 // .@..@@..
@@ -534,3 +1110,251 @@ fn count_neighbors_grid01_r5c7() {
     let actual_count = grid.count_neighboring_rolls(5, 7).unwrap();
     assert_eq!(expected_count, actual_count);
 }
+
+// Removal pass tests
+//
+
+#[test]
+fn removable_in_passes_sample_grid() {
+    let mut grid: PaperRollGrid = testhelper_make_sample_grid();
+    let removed_by_pass = grid.removable_in_passes();
+    let expected: Vec<(u32, u32)> = vec![
+        (1, 13),
+        (2, 12),
+        (3, 7),
+        (4, 5),
+        (5, 2),
+        (6, 1),
+        (7, 1),
+        (8, 1),
+        (9, 1),
+    ];
+    let actual: Vec<(u32, u32)> =
+        removed_by_pass.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(expected, actual);
+    let total: u32 = removed_by_pass.values().sum();
+    assert_eq!(43, total);
+}
+
+// Border policy tests
+//
+
+#[test]
+fn border_empty_corners() {
+    let grid: PaperRollGrid = testhelper_make_grid01();
+    assert_eq!(1, grid.count_neighboring_rolls(0, 0).unwrap());
+    assert_eq!(0, grid.count_neighboring_rolls(0, 7).unwrap());
+    assert_eq!(2, grid.count_neighboring_rolls(5, 0).unwrap());
+    assert_eq!(1, grid.count_neighboring_rolls(5, 7).unwrap());
+}
+
+#[test]
+fn border_roll_corners() {
+    let mut grid: PaperRollGrid = testhelper_make_grid01();
+    grid.set_border_policy(BorderPolicy::Roll);
+    assert_eq!(6, grid.count_neighboring_rolls(0, 0).unwrap());
+    assert_eq!(5, grid.count_neighboring_rolls(0, 7).unwrap());
+    assert_eq!(7, grid.count_neighboring_rolls(5, 0).unwrap());
+    assert_eq!(6, grid.count_neighboring_rolls(5, 7).unwrap());
+}
+
+#[test]
+fn border_roll_edge_and_interior() {
+    let mut grid: PaperRollGrid = testhelper_make_grid01();
+    grid.set_border_policy(BorderPolicy::Roll);
+    assert_eq!(5, grid.count_neighboring_rolls(0, 1).unwrap());
+    assert_eq!(3, grid.count_neighboring_rolls(1, 1).unwrap());
+}
+
+// Neighbor mode tests
+//
+
+#[test]
+fn neighbor_modes_sample_grid() {
+    let mut grid: PaperRollGrid = testhelper_make_sample_grid();
+    assert_eq!(Some(5), grid.count_neighboring_rolls(4, 6));
+    grid.set_neighbor_mode(NeighborMode::Orthogonal);
+    assert_eq!(Some(2), grid.count_neighboring_rolls(4, 6));
+    grid.set_neighbor_mode(NeighborMode::Diagonal);
+    assert_eq!(Some(3), grid.count_neighboring_rolls(4, 6));
+    grid.set_neighbor_mode(NeighborMode::All);
+    assert_eq!(Some(5), grid.count_neighboring_rolls(4, 6));
+}
+
+#[test]
+fn neighbor_modes_border_roll_corner() {
+    let mut grid: PaperRollGrid = testhelper_make_grid01();
+    grid.set_border_policy(BorderPolicy::Roll);
+    grid.set_neighbor_mode(NeighborMode::Orthogonal);
+    // (0,0) has a roll below it, and two beyond the edges
+    assert_eq!(3, grid.count_neighboring_rolls(0, 0).unwrap());
+    grid.set_neighbor_mode(NeighborMode::Diagonal);
+    // (0,0) has no diagonal roll, and three beyond the edges
+    assert_eq!(3, grid.count_neighboring_rolls(0, 0).unwrap());
+}
+
+// Roll accessibility tests
+//
+// ..@@...@
+// @..@@...
+// .@..@@..
+// ...@..@@
+// @...@..@
+// @@...@..
+
+#[test]
+fn roll_accessible() {
+    let grid: PaperRollGrid = testhelper_make_grid01();
+    assert_eq!(Some(true), grid.is_roll_accessible(0, 2, 4));
+}
+
+#[test]
+fn roll_not_accessible() {
+    let grid: PaperRollGrid = testhelper_make_grid01();
+    assert_eq!(Some(false), grid.is_roll_accessible(2, 4, 4));
+    assert_eq!(Some(true), grid.is_roll_accessible(2, 4, 5));
+}
+
+#[test]
+fn roll_accessible_empty_cell() {
+    let grid: PaperRollGrid = testhelper_make_grid01();
+    assert_eq!(None, grid.is_roll_accessible(0, 0, 4));
+}
+
+#[test]
+fn roll_accessible_out_of_grid() {
+    let grid: PaperRollGrid = testhelper_make_grid01();
+    assert_eq!(None, grid.is_roll_accessible(6, 0, 4));
+    assert_eq!(None, grid.is_roll_accessible(0, 8, 4));
+}
+
+#[test]
+fn accessibility_curve_sample_grid() {
+    let grid: PaperRollGrid = testhelper_make_sample_grid();
+    let curve = grid.accessibility_curve();
+    assert_eq!([0, 0, 1, 4, 13, 30, 41, 57, 70], curve);
+    // a single roll is surrounded on all 8 sides, so only it is
+    // missing from the last threshold
+    //
+    let total_rolls: u32 = grid
+        .rows
+        .values()
+        .map(|row| row.values().filter(|x| **x).count() as u32)
+        .sum();
+    assert_eq!(total_rolls, curve[8] + 1);
+}
+
+#[test]
+fn accessibility_report_sample_grid() {
+    let mut grid: PaperRollGrid = testhelper_make_sample_grid();
+    // the grid's own neighbor mode is not used
+    //
+    grid.set_neighbor_mode(NeighborMode::Diagonal);
+    let report = grid.accessibility_report(NeighborMode::All, 4);
+    assert_eq!(13, report.accessible_count);
+    assert_eq!(13, report.accessible_cells.len());
+    assert_eq!(
+        GridCell {
+            row_idx: 0,
+            col_idx: 2
+        },
+        report.accessible_cells[0]
+    );
+    for cell in report.accessible_cells.iter() {
+        let (ridx, cidx) = (cell.row_idx, cell.col_idx);
+        assert!(grid.has_roll(&ridx, &cidx));
+        assert!(
+            report.neighbor_counts[ridx as usize][cidx as usize] < 4
+        );
+    }
+    assert_eq!(10, report.neighbor_counts.len());
+    assert!(report.neighbor_counts.iter().all(|row| row.len() == 10));
+    assert_eq!(5, report.neighbor_counts[4][6]);
+    // (0,0) holds no roll, but its neighbors are still counted
+    //
+    assert_eq!(2, report.neighbor_counts[0][0]);
+}
+
+#[test]
+fn bitset_round_trip() {
+    let grid: PaperRollGrid = testhelper_make_sample_grid();
+    let bits = grid.to_bits();
+    // two words of dimensions, then 100 cells in two words
+    //
+    assert_eq!(4, bits.len());
+    assert_eq!([10, 10], bits[0..2]);
+    // the first row, ..@@.@@@@., from the low bit up
+    //
+    assert_eq!(0b0111101100, bits[2] & 0x3ff);
+
+    let copy = PaperRollGrid::from_bits(&bits).unwrap();
+    assert_eq!(grid.rows, copy.rows);
+    assert_eq!((10, 10), (copy.row_count, copy.col_count));
+    assert_eq!(bits, copy.to_bits());
+    assert_eq!(13, copy.accessible_rolls(4).len());
+
+    let empty = PaperRollGrid::from_bits(&[0, 0]).unwrap();
+    assert_eq!(0, empty.row_count);
+    assert!(matches!(
+        PaperRollGrid::from_bits(&bits[0..3]),
+        Err(PaperRollGridError::InvalidBitset)
+    ));
+    assert!(matches!(
+        PaperRollGrid::from_bits(&[]),
+        Err(PaperRollGridError::InvalidBitset)
+    ));
+}
+
+#[test]
+fn sample_roll_coords() {
+    let grid: PaperRollGrid = testhelper_make_sample_grid();
+    let coords = grid.roll_coords();
+    assert_eq!(71, coords.len());
+    // the first row is ..@@.@@@@. and the last @.@.@@@.@.
+    //
+    assert_eq!(
+        vec![(0, 2), (0, 3), (0, 5), (0, 6), (0, 7), (0, 8)],
+        coords[0..6]
+    );
+    assert_eq!(
+        vec![(9, 0), (9, 2), (9, 4), (9, 5), (9, 6), (9, 8)],
+        coords[65..]
+    );
+    assert!(coords.is_sorted());
+    for (ridx, cidx) in coords.iter() {
+        assert!(grid.has_roll(ridx, cidx));
+    }
+
+    let empty = PaperRollGrid::new_from_header("2 3").unwrap();
+    assert!(empty.roll_coords().is_empty());
+}
+
+#[test]
+fn remove_with_increasing_thresholds() {
+    let mut grid: PaperRollGrid = testhelper_make_sample_grid();
+    let count_rolls = |grid: &PaperRollGrid| -> u32 {
+        grid.rows
+            .values()
+            .map(|row| row.values().filter(|x| **x).count() as u32)
+            .sum()
+    };
+    let total_rolls = count_rolls(&grid);
+    // the strictest pass removes the roll with fewer than two
+    // neighbors, as in the accessibility curve
+    //
+    let strictest = grid.accessible_rolls(2);
+    assert_eq!(1, strictest.len());
+    let (ridx, cidx) = (strictest[0].row_idx, strictest[0].col_idx);
+
+    let passes = grid.remove_with_thresholds(&[2, 3, 4, 4]);
+    assert_eq!(vec![(2, 1), (3, 3), (4, 12), (4, 10)], passes);
+    assert!(!grid.has_roll(&ridx, &cidx));
+    let removed: u32 = passes.iter().map(|(_, n)| n).sum();
+    assert_eq!(26, removed);
+    assert_eq!(total_rolls, count_rolls(&grid) + removed);
+
+    // an empty sequence removes nothing
+    //
+    assert!(grid.remove_with_thresholds(&[]).is_empty());
+    assert_eq!(total_rolls - 26, count_rolls(&grid));
+}
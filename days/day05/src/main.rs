@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
@@ -11,6 +13,9 @@ use clap::Parser;
 ///
 #[derive(Parser)]
 struct Cli {
+    /// Also list each merged range, sorted ascending
+    #[arg(long = "show-ranges")]
+    show_ranges: bool,
     /// The path to the file containing battery bank specs
     path: PathBuf,
 }
@@ -21,6 +26,51 @@ struct Cli {
 struct IngredientRange {
     start: u64,
     end: u64,
+    // indices of the original ranges that formed this range,
+    // only populated when the IngredientDB tracks provenance
+    //
+    provenance: BTreeSet<usize>,
+}
+
+// ranges are ordered by start, then end; the provenance is
+// bookkeeping and takes no part in comparisons
+//
+impl PartialEq for IngredientRange {
+    fn eq(&self, other: &Self) -> bool {
+        (self.start, self.end) == (other.start, other.end)
+    }
+}
+
+impl Eq for IngredientRange {}
+
+impl PartialOrd for IngredientRange {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IngredientRange {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.start, self.end).cmp(&(other.start, other.end))
+    }
+}
+
+// how one ingredient range relates to another
+//
+#[derive(Debug, PartialEq)]
+enum RangeRelation {
+    // the ranges share no ids
+    Disjoint,
+    // the ranges have the same start and end
+    Equal,
+    // the other range lies entirely within this range
+    Contains,
+    // this range lies entirely within the other range
+    ContainedBy,
+    // the other range overlaps the start of this range
+    OverlapsStart,
+    // the other range overlaps the end of this range
+    OverlapsEnd,
 }
 
 // functions associated with struct IngredientRange
@@ -38,6 +88,7 @@ impl IngredientRange {
         IngredientRange {
             start: start,
             end: end,
+            provenance: BTreeSet::new(),
         }
     }
 
@@ -47,11 +98,13 @@ impl IngredientRange {
         IngredientRange {
             start: self.start,
             end: self.end,
+            provenance: self.provenance.clone(),
         }
     }
 
     // Returns true if id is within the range; otherwise false
     //
+    #[allow(dead_code)]
     fn contains(&self, id: u64) -> bool {
         if id < self.start {
             false
@@ -62,43 +115,24 @@ impl IngredientRange {
         }
     }
 
-    fn overlaps_range(&self, other: &IngredientRange) -> bool {
-        if (other.start >= self.start)
-            && (other.start <= self.end)
-            && (other.end > self.end)
+    // Determine how the other range relates to this range
+    //
+    fn relationship(&self, other: &IngredientRange) -> RangeRelation {
+        if (other.end < self.start) || (other.start > self.end) {
+            RangeRelation::Disjoint
+        } else if (other.start == self.start) && (other.end == self.end)
         {
-            true
-        } else if (other.end >= self.start)
-            && (other.end <= self.end)
-            && (other.start < self.start)
+            RangeRelation::Equal
+        } else if (other.start >= self.start) && (other.end <= self.end)
         {
-            true
-        } else {
-            false
-        }
-    }
-
-    fn contains_range(&self, other: &IngredientRange) -> bool {
-        if (other.start >= self.start)
-            && (other.start <= self.end)
-            && (other.end >= self.start)
-            && (other.end <= self.end)
-        {
-            true
-        } else {
-            false
-        }
-    }
-
-    fn contained_by_range(&self, other: &IngredientRange) -> bool {
-        if (self.start >= other.start)
-            && (self.start <= other.end)
-            && (self.end >= other.start)
-            && (self.end <= other.end)
+            RangeRelation::Contains
+        } else if (other.start <= self.start) && (other.end >= self.end)
         {
-            true
+            RangeRelation::ContainedBy
+        } else if other.start < self.start {
+            RangeRelation::OverlapsStart
         } else {
-            false
+            RangeRelation::OverlapsEnd
         }
     }
 
@@ -107,6 +141,9 @@ impl IngredientRange {
         let new_end = u64::max(self.end, other.end);
         self.start = new_start;
         self.end = new_end;
+        for idx in other.provenance.iter() {
+            self.provenance.insert(*idx);
+        }
     }
 }
 
@@ -116,7 +153,14 @@ struct IngredientDB {
     // A list of ingredient ranges in the order added
     //
     original_ranges: Vec<IngredientRange>,
+    // the disjoint ranges formed by merging the original ranges,
+    // kept sorted
+    //
     merged_ranges: Vec<IngredientRange>,
+    // whether merged ranges record the original ranges
+    // that formed them
+    //
+    track_provenance: bool,
 }
 
 // functions associated with IngredientDB
@@ -130,30 +174,188 @@ impl IngredientDB {
         IngredientDB {
             original_ranges: list1,
             merged_ranges: list2,
+            track_provenance: false,
         }
     }
 
+    // constructor for a database whose merged ranges record
+    // the original ranges that formed them
+    //
+    #[allow(dead_code)]
+    fn new_with_provenance() -> Self {
+        let mut db = IngredientDB::new();
+        db.track_provenance = true;
+        db
+    }
+
     // add a new fresh ingredient range
     //
     fn add_range(&mut self, start: u64, end: u64) {
+        let original_idx = self.original_ranges.len();
         let ir = IngredientRange::new(start, end);
         self.original_ranges.push(ir);
-        let ir = IngredientRange::new(start, end);
+        let mut ir = IngredientRange::new(start, end);
+        if self.track_provenance {
+            ir.provenance.insert(original_idx);
+        }
         self.update_merged_ranges(&ir);
     }
 
+    // the indices of the original ranges that formed the merged
+    // range; empty unless the database tracks provenance
+    //
+    // Will panic if merged_index is not a merged range index.
+    //
+    #[allow(dead_code)]
+    fn provenance_of(&self, merged_index: usize) -> &BTreeSet<usize> {
+        &self.merged_ranges[merged_index].provenance
+    }
+
+    // iterate over the merged ranges as (start, end) pairs,
+    // sorted ascending
+    //
+    fn fresh_ranges(&self) -> impl Iterator<Item = (u64, u64)> {
+        self.merged_ranges.iter().map(|ir| (ir.start, ir.end))
+    }
+
+    // iterate over the gaps of spoiled ids between consecutive
+    // merged ranges as (start, end) pairs, sorted ascending
+    //
+    #[allow(dead_code)]
+    fn spoiled_gaps(&self) -> impl Iterator<Item = (u64, u64)> {
+        let ranges: Vec<(u64, u64)> = self.fresh_ranges().collect();
+        let mut gaps: Vec<(u64, u64)> = Vec::new();
+        for pair in ranges.windows(2) {
+            let (_, prev_end) = pair[0];
+            let (next_start, _) = pair[1];
+            // ranges that touch but don't overlap leave no gap
+            //
+            if prev_end + 1 < next_start {
+                gaps.push((prev_end + 1, next_start - 1));
+            }
+        }
+        gaps.into_iter()
+    }
+
+    // the total size of the original ranges, counting the ids in
+    // overlapping ranges once per range. A total too large for a
+    // u64 stops at u64::MAX.
+    //
+    fn original_span(&self) -> u64 {
+        let mut span: u64 = 0;
+        for ir in self.original_ranges.iter() {
+            span = (ir.end - ir.start)
+                .checked_add(1)
+                .and_then(|size| span.checked_add(size))
+                .unwrap_or(u64::MAX);
+        }
+        span
+    }
+
+    // the total number of fresh ingredient ids, and whether it
+    // saturated. The merged ranges are disjoint, so the total only
+    // overflows when they cover every u64; it then stops at
+    // u64::MAX.
+    //
+    fn fresh_total(&self) -> (u64, bool) {
+        let mut total: u64 = 0;
+        for ir in self.merged_ranges.iter() {
+            match (ir.end - ir.start)
+                .checked_add(1)
+                .and_then(|size| total.checked_add(size))
+            {
+                Some(sum) => total = sum,
+                None => return (u64::MAX, true),
+            }
+        }
+        (total, false)
+    }
+
+    // count the fresh ingredient ids between lo and hi, inclusive
+    //
+    #[allow(dead_code)]
+    fn fresh_count_in(&self, lo: u64, hi: u64) -> u64 {
+        let mut count: u64 = 0;
+        for ir in self.merged_ranges.iter() {
+            let start = u64::max(ir.start, lo);
+            let end = u64::min(ir.end, hi);
+            if start <= end {
+                count =
+                    count.saturating_add(end - start).saturating_add(1);
+            }
+        }
+        count
+    }
+
+    // the fraction of the ids between lo and hi, inclusive, that
+    // are fresh; an empty domain (lo > hi) has no fresh ids, so
+    // its fraction is 0.0
+    //
+    #[allow(dead_code)]
+    fn fresh_fraction(&self, lo: u64, hi: u64) -> f64 {
+        if lo > hi {
+            return 0.0;
+        }
+        let domain_size = ((hi - lo) as f64) + 1.0;
+        (self.fresh_count_in(lo, hi) as f64) / domain_size
+    }
+
+    // check whether the id falls within any of the ranges
+    //
+    fn contains(&self, id: u64) -> bool {
+        self.fresh_range_for(id).is_some()
+    }
+
+    // Find the (start, end) of the merged range the id falls
+    // within, with a binary search of the sorted merged ranges.
+    //
+    // Returns None if the id is spoiled.
+    //
+    fn fresh_range_for(&self, id: u64) -> Option<(u64, u64)> {
+        let idx = self
+            .merged_ranges
+            .binary_search_by(|ir| {
+                if ir.end < id {
+                    Ordering::Less
+                } else if ir.start > id {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .ok()?;
+        let ir = &self.merged_ranges[idx];
+        Some((ir.start, ir.end))
+    }
+
     // check whether the ingredient is known to be fresh
     //
     fn is_fresh(&self, id: u64) -> bool {
         // println!("Checking freshness of {}", id);
-        let mut result: bool = false;
-        for thing in self.merged_ranges.iter() {
-            if thing.contains(id) {
-                result = true;
-                break;
+        self.contains(id)
+    }
+
+    // Put the merged ranges in canonical form: sorted, with no two
+    // overlapping or adjacent, e.g. 3-5 and 6-8 become 3-8. Adding
+    // ranges one at a time only merges ranges that overlap, so call
+    // this after adding a batch of them.
+    //
+    fn normalize(&mut self) {
+        let mut ranges: Vec<IngredientRange> =
+            std::mem::take(&mut self.merged_ranges);
+        ranges.sort();
+        for ir in ranges {
+            match self.merged_ranges.last_mut() {
+                Some(last)
+                    if ir.start <= last.end.saturating_add(1) =>
+                {
+                    last.merge_with(&ir);
+                }
+                _ => {
+                    self.merged_ranges.push(ir);
+                }
             }
         }
-        result
     }
 
     fn update_merged_ranges(&mut self, ir: &IngredientRange) {
@@ -161,9 +363,7 @@ impl IngredientDB {
         let mut unchanged_ranges: Vec<IngredientRange> = Vec::new();
         let mut new_range: IngredientRange = ir.copy();
         for thing in self.merged_ranges.iter() {
-            if thing.contains_range(&new_range)
-                || thing.contained_by_range(&new_range)
-                || thing.overlaps_range(&new_range)
+            if RangeRelation::Disjoint != thing.relationship(&new_range)
             {
                 ir_was_merged = true;
                 let mut merged_range = thing.copy();
@@ -191,89 +391,567 @@ impl IngredientDB {
             }
             self.merged_ranges.push(new_range);
         }
+        self.merged_ranges.sort();
     }
 }
 
-// Binary crate entry point
+// build a database from (start, end) pairs, e.g.
+// `ranges.into_iter().collect()`
 //
-fn main() -> Result<()> {
-    let args = Cli::parse();
-    let path = &args.path;
+// Rather than merging the ranges one at a time, they are
+// normalized in a single sweep, so overlapping and adjacent
+// ranges are merged.
+//
+// Will panic if any start > end.
+//
+impl FromIterator<(u64, u64)> for IngredientDB {
+    fn from_iter<I: IntoIterator<Item = (u64, u64)>>(iter: I) -> Self {
+        let mut db = IngredientDB::new();
+        for (start, end) in iter {
+            db.original_ranges.push(IngredientRange::new(start, end));
+        }
+        db.merged_ranges =
+            db.original_ranges.iter().map(|ir| ir.copy()).collect();
+        db.normalize();
+        db
+    }
+}
 
-    let f = File::open(path).with_context(|| {
-        format!("Could not open `{}`", path.display())
-    })?;
-    let rdr = BufReader::new(f);
-    let lines = rdr.lines();
+// the outcome of processing an ingredient database file
+//
+struct InventorySummary {
+    db: IngredientDB,
+    fresh_ingredient_count: u64,
+    spoiled_ingredient_count: u64,
+    total_ingredient_count: u64,
+    total_range_count: u64,
+}
 
-    // populate the DB with fresh ingredient ranges. To do
-    // that, read in the ranges until a blank line is encountered
-    //
-    let mut fresh_ingredient_count: u64 = 0;
-    let mut total_range_count: u64 = 0;
-    let mut total_ingredient_count: u64 = 0;
-    let mut spoiled_ingredient_count: u64 = 0;
-    let mut db = IngredientDB::new();
+// populate the DB with fresh ingredient ranges and check the
+// ingredient ids against it. The ranges come first and end at
+// the first blank line; the ingredient ids follow.
+//
+fn solve(lines: &[String]) -> InventorySummary {
+    let mut summary = InventorySummary {
+        db: IngredientDB::new(),
+        fresh_ingredient_count: 0,
+        spoiled_ingredient_count: 0,
+        total_ingredient_count: 0,
+        total_range_count: 0,
+    };
     let mut process_ids: bool = false;
     for line in lines {
-        let line = line.with_context(|| {
-            format!("Problem reading from `{}`", path.display())
-        })?;
         let line = line.trim();
         if 0 == line.len() {
+            if !process_ids {
+                summary.db.normalize();
+            }
             process_ids = true;
             continue;
         }
         if !process_ids {
-            total_range_count += 1;
+            summary.total_range_count += 1;
             // process ranges
             //
             let parts: Vec<&str> = line.split('-').collect();
             let start: u64 = parts.get(0).unwrap().parse().unwrap();
             let end: u64 = parts.get(1).unwrap().parse().unwrap();
-            db.add_range(start, end);
+            summary.db.add_range(start, end);
         } else {
-            total_ingredient_count += 1;
+            summary.total_ingredient_count += 1;
             let id: u64 = line.parse().unwrap();
-            if db.is_fresh(id) {
-                fresh_ingredient_count += 1;
+            if summary.db.is_fresh(id) {
+                summary.fresh_ingredient_count += 1;
                 // println!("FRESH: {}", id);
             } else {
-                spoiled_ingredient_count += 1;
+                summary.spoiled_ingredient_count += 1;
                 // println!("spoiled: {}", id);
             }
         }
     }
+    if !process_ids {
+        summary.db.normalize();
+    }
+    summary
+}
+
+// Format the summary for display, one line per fact. When
+// show_ranges is set, the merged ranges are listed at the end.
+//
+fn report(
+    summary: &InventorySummary,
+    show_ranges: bool,
+) -> Vec<String> {
+    let db = &summary.db;
 
     // Calculate total possible fresh ingredients
     //
-    let mut total_possible_fresh_ingredients: u64 = 0;
-    for ir in db.merged_ranges.iter() {
-        let size_of_range: u64 = (ir.end + 1) - ir.start;
-        total_possible_fresh_ingredients += size_of_range;
-    }
+    let (total_possible_fresh_ingredients, domain_saturated) =
+        db.fresh_total();
     let total_merged_ranges: u64 =
         db.merged_ranges.len().try_into().unwrap();
 
+    let mut lines: Vec<String> = vec![
+        format!(
+            "The count of fresh ingredients is {}",
+            summary.fresh_ingredient_count
+        ),
+        format!(
+            "The count of spoiled ingredients is {}",
+            summary.spoiled_ingredient_count
+        ),
+        format!(
+            "The count of total ingredients is {}",
+            summary.total_ingredient_count
+        ),
+        format!("The count of ranges is {}", summary.total_range_count),
+        format!(
+            "The count of merged ranges is {}",
+            total_merged_ranges
+        ),
+        format!(
+            "The total possible fresh ingredients is {}",
+            total_possible_fresh_ingredients
+        ),
+        format!(
+            "The total span of the original ranges is {}",
+            db.original_span()
+        ),
+    ];
+    if domain_saturated {
+        lines.push(
+            "The fresh ranges cover every id, one more than the largest u64"
+                .to_string(),
+        );
+    }
+    if show_ranges {
+        lines.push("The merged ranges are:".to_string());
+        for (start, end) in db.fresh_ranges() {
+            lines.push(format!("  {}-{}", start, end));
+        }
+    }
+    lines
+}
+
+// Binary crate entry point
+//
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let path = &args.path;
+
+    let f = File::open(path).with_context(|| {
+        format!("Could not open `{}`", path.display())
+    })?;
+    let rdr = BufReader::new(f);
+    let mut lines: Vec<String> = Vec::new();
+    for line in rdr.lines() {
+        let line = line.with_context(|| {
+            format!("Problem reading from `{}`", path.display())
+        })?;
+        lines.push(line);
+    }
+
+    let summary = solve(&lines);
+
     // Display the total number of fresh ingredients
     //
-    println!(
-        "The count of fresh ingredients is {}",
-        fresh_ingredient_count
+    for line in report(&summary, args.show_ranges) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+// IngredientRange relationship tests
+//
+
+#[test]
+fn relationship_disjoint() {
+    let ir = IngredientRange::new(10, 14);
+    assert_eq!(
+        RangeRelation::Disjoint,
+        ir.relationship(&IngredientRange::new(3, 5))
+    );
+    assert_eq!(
+        RangeRelation::Disjoint,
+        ir.relationship(&IngredientRange::new(15, 20))
+    );
+}
+
+#[test]
+fn relationship_equal() {
+    let ir = IngredientRange::new(10, 14);
+    assert_eq!(
+        RangeRelation::Equal,
+        ir.relationship(&IngredientRange::new(10, 14))
+    );
+}
+
+#[test]
+fn relationship_contains() {
+    let ir = IngredientRange::new(10, 14);
+    assert_eq!(
+        RangeRelation::Contains,
+        ir.relationship(&IngredientRange::new(11, 13))
+    );
+    assert_eq!(
+        RangeRelation::Contains,
+        ir.relationship(&IngredientRange::new(10, 12))
+    );
+    assert_eq!(
+        RangeRelation::Contains,
+        ir.relationship(&IngredientRange::new(14, 14))
+    );
+}
+
+#[test]
+fn relationship_contained_by() {
+    let ir = IngredientRange::new(10, 14);
+    assert_eq!(
+        RangeRelation::ContainedBy,
+        ir.relationship(&IngredientRange::new(9, 15))
     );
-    println!(
-        "The count of spoiled ingredients is {}",
-        spoiled_ingredient_count
+    assert_eq!(
+        RangeRelation::ContainedBy,
+        ir.relationship(&IngredientRange::new(10, 15))
     );
-    println!(
-        "The count of total ingredients is {}",
-        total_ingredient_count
+}
+
+#[test]
+fn relationship_overlaps_start() {
+    let ir = IngredientRange::new(10, 14);
+    assert_eq!(
+        RangeRelation::OverlapsStart,
+        ir.relationship(&IngredientRange::new(8, 12))
+    );
+    assert_eq!(
+        RangeRelation::OverlapsStart,
+        ir.relationship(&IngredientRange::new(8, 10))
+    );
+}
+
+#[test]
+fn relationship_overlaps_end() {
+    let ir = IngredientRange::new(10, 14);
+    assert_eq!(
+        RangeRelation::OverlapsEnd,
+        ir.relationship(&IngredientRange::new(12, 16))
     );
-    println!("The count of ranges is {}", total_range_count);
-    println!("The count of merged ranges is {}", total_merged_ranges);
-    println!(
+    assert_eq!(
+        RangeRelation::OverlapsEnd,
+        ir.relationship(&IngredientRange::new(14, 16))
+    );
+}
+
+#[test]
+fn merge_range_spanning_existing_range() {
+    let mut db = IngredientDB::new();
+    db.add_range(10, 14);
+    db.add_range(3, 5);
+    db.add_range(1, 20);
+    assert_eq!(1, db.merged_ranges.len());
+    assert_eq!(1, db.merged_ranges[0].start);
+    assert_eq!(20, db.merged_ranges[0].end);
+}
+
+// IngredientRange ordering tests
+//
+
+#[test]
+fn ranges_sort_by_start_then_end() {
+    let mut ranges: Vec<IngredientRange> = vec![
+        IngredientRange::new(10, 14),
+        IngredientRange::new(3, 5),
+        IngredientRange::new(10, 12),
+        IngredientRange::new(4, 20),
+    ];
+    ranges.sort();
+    let sorted: Vec<(u64, u64)> =
+        ranges.iter().map(|ir| (ir.start, ir.end)).collect();
+    assert_eq!(vec![(3, 5), (4, 20), (10, 12), (10, 14)], sorted);
+
+    // provenance takes no part in comparisons
+    //
+    let a = IngredientRange::new(3, 5);
+    let mut b = IngredientRange::new(3, 5);
+    b.provenance.insert(7);
+    assert_eq!(a, b);
+    assert_eq!(Ordering::Equal, a.cmp(&b));
+    assert!(a < IngredientRange::new(3, 6));
+}
+
+// IngredientDB provenance tests
+//
+
+#[test]
+fn provenance_of_three_overlapping_ranges() {
+    let mut db = IngredientDB::new_with_provenance();
+    db.add_range(3, 5);
+    db.add_range(7, 10);
+    db.add_range(4, 8);
+    assert_eq!(1, db.merged_ranges.len());
+    let expected: BTreeSet<usize> = BTreeSet::from([0, 1, 2]);
+    assert_eq!(&expected, db.provenance_of(0));
+}
+
+#[test]
+fn provenance_of_separate_ranges() {
+    let mut db = IngredientDB::new_with_provenance();
+    db.add_range(3, 5);
+    db.add_range(10, 14);
+    db.add_range(12, 18);
+    assert_eq!(2, db.merged_ranges.len());
+    assert_eq!(&BTreeSet::from([0]), db.provenance_of(0));
+    assert_eq!(&BTreeSet::from([1, 2]), db.provenance_of(1));
+}
+
+#[test]
+fn provenance_not_tracked_by_default() {
+    let mut db = IngredientDB::new();
+    db.add_range(3, 5);
+    db.add_range(4, 8);
+    assert!(db.provenance_of(0).is_empty());
+}
+
+// fresh fraction tests
+//
+
+#[test]
+fn fresh_fraction_half_of_domain() {
+    let mut db = IngredientDB::new();
+    db.add_range(1, 5);
+    db.add_range(11, 15);
+    assert_eq!(10, db.fresh_count_in(1, 20));
+    assert_eq!(0.5, db.fresh_fraction(1, 20));
+}
+
+#[test]
+fn fresh_fraction_none_of_domain() {
+    let mut db = IngredientDB::new();
+    db.add_range(1, 5);
+    assert_eq!(0.0, db.fresh_fraction(10, 20));
+    assert_eq!(0.0, db.fresh_fraction(20, 10));
+}
+
+#[test]
+fn fresh_fraction_clamps_to_domain() {
+    let mut db = IngredientDB::new();
+    db.add_range(16, 30);
+    assert_eq!(5, db.fresh_count_in(11, 20));
+    assert_eq!(0.5, db.fresh_fraction(11, 20));
+}
+
+#[test]
+fn spoiled_gaps_between_ranges() {
+    let mut db = IngredientDB::new();
+    db.add_range(30, 40);
+    db.add_range(3, 5);
+    db.add_range(10, 20);
+    let gaps: Vec<(u64, u64)> = db.spoiled_gaps().collect();
+    assert_eq!(vec![(6, 9), (21, 29)], gaps);
+
+    // a range just after another leaves no gap
+    //
+    db.add_range(41, 45);
+    let gaps: Vec<(u64, u64)> = db.spoiled_gaps().collect();
+    assert_eq!(vec![(6, 9), (21, 29)], gaps);
+}
+
+#[test]
+fn original_span_counts_overlap_twice() {
+    let mut db = IngredientDB::new();
+    db.add_range(10, 19);
+    db.add_range(15, 24);
+    assert_eq!(20, db.original_span());
+    let merged_span = db.fresh_count_in(0, u64::MAX);
+    assert_eq!(15, merged_span);
+    // the ranges overlap on 15-19
+    //
+    assert_eq!(5, db.original_span() - merged_span);
+}
+
+#[test]
+fn fresh_total_saturates_over_whole_domain() {
+    let mut db = IngredientDB::new();
+    db.add_range(10, 19);
+    db.add_range(30, 39);
+    assert_eq!((20, false), db.fresh_total());
+
+    let mut db = IngredientDB::new();
+    db.add_range(0, u64::MAX);
+    assert_eq!((u64::MAX, true), db.fresh_total());
+    assert_eq!(u64::MAX, db.fresh_count_in(0, u64::MAX));
+    assert_eq!(u64::MAX, db.original_span());
+
+    // one id short of the whole domain still fits
+    //
+    let mut db = IngredientDB::new();
+    db.add_range(1, u64::MAX);
+    assert_eq!((u64::MAX, false), db.fresh_total());
+
+    // the report flags the saturated total rather than wrapping
+    //
+    let lines: Vec<String> =
+        vec![format!("0-{}", u64::MAX), String::new()];
+    let lines = report(&solve(&lines), false);
+    assert!(lines.contains(&format!(
         "The total possible fresh ingredients is {}",
-        total_possible_fresh_ingredients
+        u64::MAX
+    )));
+    assert!(lines.contains(
+        &"The fresh ranges cover every id, one more than the largest u64"
+            .to_string()
+    ));
+    let lines = report(&solve(&testhelper_sample_lines()), false);
+    assert!(!lines.iter().any(|x| x.contains("cover every id")));
+}
+
+// solve/report tests
+//
+
+#[cfg(test)]
+fn testhelper_sample_lines() -> Vec<String> {
+    let raw_input = "3-5
+10-14
+16-20
+12-18
+
+1
+5
+8
+11
+17
+32
+";
+    raw_input.lines().map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn show_ranges_lists_merged_sample_ranges() {
+    let summary = solve(&testhelper_sample_lines());
+    assert_eq!(3, summary.fresh_ingredient_count);
+    let expected: Vec<(u64, u64)> = vec![(3, 5), (10, 20)];
+    let ranges: Vec<(u64, u64)> = summary.db.fresh_ranges().collect();
+    assert_eq!(expected, ranges);
+
+    let lines = report(&summary, true);
+    let idx = lines
+        .iter()
+        .position(|x| x == "The merged ranges are:")
+        .unwrap();
+    assert_eq!(vec!["  3-5", "  10-20"], lines[idx + 1..].to_vec());
+    assert!(
+        !report(&summary, false)
+            .contains(&"The merged ranges are:".to_string())
     );
-    Ok(())
+}
+
+// test that contains and is_fresh agree
+//
+#[test]
+fn contains_agrees_with_is_fresh() {
+    let summary = solve(&testhelper_sample_lines());
+    for id in 0..=25 {
+        assert_eq!(summary.db.is_fresh(id), summary.db.contains(id));
+    }
+    assert!(summary.db.contains(3));
+    assert!(summary.db.contains(20));
+    assert!(!summary.db.contains(9));
+    assert!(!summary.db.contains(21));
+}
+
+// test finding the merged range a fresh id falls within
+//
+#[test]
+fn fresh_range_for_merged_interval() {
+    let summary = solve(&testhelper_sample_lines());
+    // 10-14, 16-20 and 12-18 merge into 10-20
+    //
+    assert_eq!(Some((10, 20)), summary.db.fresh_range_for(15));
+    assert_eq!(Some((10, 20)), summary.db.fresh_range_for(10));
+    assert_eq!(Some((3, 5)), summary.db.fresh_range_for(5));
+    assert_eq!(None, summary.db.fresh_range_for(9));
+    assert_eq!(None, summary.db.fresh_range_for(21));
+}
+
+// test that normalizing gives the same canonical ranges whatever
+// order the ranges were added in
+//
+#[test]
+fn normalize_ranges_added_in_reverse() {
+    let mut ranges: Vec<(u64, u64)> = vec![
+        (3, 5),
+        (4, 4),
+        (10, 14),
+        (12, 18),
+        (16, 20),
+        (25, 29),
+        (30, 30),
+        (31, 35),
+        (33, 40),
+    ];
+    let mut sorted = IngredientDB::new_with_provenance();
+    for (start, end) in ranges.iter() {
+        sorted.add_range(*start, *end);
+    }
+    sorted.normalize();
+    ranges.reverse();
+    let mut reversed = IngredientDB::new_with_provenance();
+    for (start, end) in ranges.iter() {
+        reversed.add_range(*start, *end);
+    }
+    // adjacent ranges are not merged as they are added
+    //
+    assert_eq!(5, reversed.fresh_ranges().count());
+    reversed.normalize();
+
+    let expected: Vec<(u64, u64)> = vec![(3, 5), (10, 20), (25, 40)];
+    assert_eq!(expected, sorted.fresh_ranges().collect::<Vec<_>>());
+    assert_eq!(expected, reversed.fresh_ranges().collect::<Vec<_>>());
+    // 25-29, 30-30, 31-35 and 33-40 were added first, in reverse
+    //
+    assert_eq!(
+        &BTreeSet::from([0, 1, 2, 3]),
+        reversed.provenance_of(2)
+    );
+    assert_eq!(&BTreeSet::from([5, 6, 7, 8]), sorted.provenance_of(2));
+
+    // normalizing again changes nothing
+    //
+    reversed.normalize();
+    assert_eq!(expected, reversed.fresh_ranges().collect::<Vec<_>>());
+}
+
+// test collecting a database from range tuples
+//
+#[test]
+fn collect_from_range_tuples() {
+    let ranges: Vec<(u64, u64)> = vec![
+        (16, 20),
+        (30, 30),
+        (3, 5),
+        (12, 18),
+        (25, 29),
+        (10, 14),
+        (4, 4),
+        (31, 35),
+        (33, 40),
+    ];
+    let db: IngredientDB = ranges.iter().copied().collect();
+    let expected: Vec<(u64, u64)> = vec![(3, 5), (10, 20), (25, 40)];
+    let merged: Vec<(u64, u64)> = db.fresh_ranges().collect();
+    assert_eq!(expected, merged);
+    assert_eq!(3 + 11 + 16, db.fresh_count_in(0, u64::MAX));
+    assert_eq!(9, db.original_ranges.len());
+
+    // adding the ranges one at a time and normalizing gives the
+    // same ranges
+    //
+    let mut one_at_a_time = IngredientDB::new();
+    for (start, end) in ranges {
+        one_at_a_time.add_range(start, end);
+    }
+    one_at_a_time.normalize();
+    let merged: Vec<(u64, u64)> =
+        one_at_a_time.fresh_ranges().collect();
+    assert_eq!(expected, merged);
 }
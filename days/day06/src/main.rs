@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
@@ -11,17 +12,233 @@ use clap::Parser;
 ///
 #[derive(Parser)]
 struct Cli {
+    /// Parse the last non-blank row as the operators for each
+    /// problem, rather than inferring them from the columns
+    #[arg(long = "operator-row")]
+    operator_row: bool,
+    /// Fold each problem's terms right to left, e.g. a-(b-c),
+    /// rather than left to right, e.g. (a-b)-c
+    #[arg(long = "fold-right")]
+    fold_right: bool,
+    /// Read an operator under each number, combining it with the
+    /// number to its right, and evaluate each problem with * and /
+    /// before + and -
+    #[arg(long = "precedence", conflicts_with = "fold_right")]
+    precedence: bool,
+    /// The problems run along the rows rather than down the
+    /// columns; transpose the input before parsing it
+    #[arg(long = "transpose")]
+    transpose: bool,
+    /// Print each column's inferred kind, with its number and
+    /// operator, before solving
+    #[arg(long = "describe-columns")]
+    describe_columns: bool,
+    /// Read the columns right to left and, failing that, left to
+    /// right, keeping the first reading in which every problem
+    /// parses with an operator between each pair of numbers
+    #[arg(
+        long = "auto-orient",
+        conflicts_with_all = ["transpose", "operator_row"]
+    )]
+    auto_orient: bool,
+    /// Print each problem's answer, signed, and how many answers
+    /// are negative, zero and positive
+    #[arg(long = "sign-report")]
+    sign_report: bool,
     /// The path to the file containing battery bank specs
     path: PathBuf,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum CephMathOperation {
     Add,
     Multiply,
+    Subtract,
+    Divide,
     Unknown,
 }
 
+impl CephMathOperation {
+    // a lower case name for the operation, e.g. "add"
+    //
+    fn name(&self) -> &'static str {
+        match self {
+            CephMathOperation::Add => "add",
+            CephMathOperation::Multiply => "multiply",
+            CephMathOperation::Subtract => "subtract",
+            CephMathOperation::Divide => "divide",
+            CephMathOperation::Unknown => "unknown",
+        }
+    }
+
+    // apply the operation to a pair of values; an error if dividing
+    // by zero or the result does not fit in an i64
+    //
+    fn apply(
+        &self,
+        lhs: i64,
+        rhs: i64,
+    ) -> Result<i64, ArithmeticError> {
+        let result = match self {
+            CephMathOperation::Add => lhs.checked_add(rhs),
+            CephMathOperation::Multiply => lhs.checked_mul(rhs),
+            CephMathOperation::Subtract => lhs.checked_sub(rhs),
+            CephMathOperation::Divide => {
+                if 0 == rhs {
+                    return Err(ArithmeticError::DivideByZero);
+                }
+                lhs.checked_div(rhs)
+            }
+            CephMathOperation::Unknown => {
+                panic!("UNKNOWN OPERATION");
+            }
+        };
+        result.ok_or(ArithmeticError::Overflow)
+    }
+}
+
+// Why an operation could not be applied to a pair of values
+//
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArithmeticError {
+    DivideByZero,
+    Overflow,
+}
+
+// The order in which a problem's terms are combined. Terms are
+// kept in the order they were read (add_columns reads the columns
+// right to left). Folding left to right gives (a-b)-c; folding
+// right to left gives a-(b-c). The puzzle only uses addition and
+// multiplication, which give the same answer in either order.
+//
+// Precedence evaluates the terms as an expression, applying * and /
+// before + and -, so a problem may mix operators, one between each
+// pair of terms. It also changes how add_columns reads operators:
+// see there.
+//
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FoldOrder {
+    LeftToRight,
+    RightToLeft,
+    Precedence,
+}
+
+// The order in which add_columns reads the columns, and so the
+// order of each problem's terms. The cephalopods read right to
+// left.
+//
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnDirection {
+    RightToLeft,
+    LeftToRight,
+}
+
+impl ColumnDirection {
+    // the direction in words, e.g. "right to left"
+    //
+    fn describe(&self) -> &'static str {
+        match self {
+            ColumnDirection::RightToLeft => "right to left",
+            ColumnDirection::LeftToRight => "left to right",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CephMathError {
+    // the problem's position, counting from 1 at the left
+    MissingOperation(usize),
+    // the column's position, counting from 1 at the left
+    ValueOverflow(u64),
+    // the problem's position, counting from 1 at the left
+    MissingOperand(usize),
+    // the column's position, counting from 1 at the left, and the
+    // character that cannot go there
+    UnexpectedCharacter(u64, char),
+    // the problem's position, counting from 1 at the left
+    DivideByZero(usize),
+    // the problem's position, counting from 1 at the left
+    AnswerOverflow(usize),
+    // the number of operators in the operator row, and of problems
+    OperatorCount(usize, usize),
+    // the operator's position in the operator row, counting from 1
+    // at the left, and the operator
+    UnknownOperator(usize, String),
+    // the grand total of the problems' answers
+    TotalOverflow,
+}
+
+impl fmt::Display for CephMathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CephMathError::MissingOperation(position) => {
+                write!(
+                    f,
+                    "Problem {} (counting from the left) has no operator; is the operator line too short?",
+                    position
+                )
+            }
+            CephMathError::MissingOperand(position) => {
+                write!(
+                    f,
+                    "Problem {} (counting from the left) has an operator but only one number",
+                    position
+                )
+            }
+            CephMathError::UnexpectedCharacter(column, c) => {
+                write!(
+                    f,
+                    "Column {} has '{}' where no number or operator can go",
+                    column, c
+                )
+            }
+            CephMathError::DivideByZero(position) => {
+                write!(
+                    f,
+                    "Problem {} (counting from the left) divides by zero",
+                    position
+                )
+            }
+            CephMathError::OperatorCount(operators, problems) => {
+                write!(
+                    f,
+                    "The operator row has {} operators for {} problems",
+                    operators, problems
+                )
+            }
+            CephMathError::UnknownOperator(column, operator) => {
+                write!(
+                    f,
+                    "Operator {} (counting from the left), '{}', is not one of + - * /",
+                    column, operator
+                )
+            }
+            CephMathError::AnswerOverflow(position) => {
+                write!(
+                    f,
+                    "The answer to problem {} (counting from the left) is too large for a 64-bit integer",
+                    position
+                )
+            }
+            CephMathError::TotalOverflow => {
+                write!(
+                    f,
+                    "The grand total of problem answers is too large for a 64-bit integer"
+                )
+            }
+            CephMathError::ValueOverflow(column) => {
+                write!(
+                    f,
+                    "The number in column {} is too large for a 64-bit integer",
+                    column
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CephMathError {}
+
 #[derive(Debug)]
 enum InputColumnKind {
     Empty,
@@ -51,7 +268,7 @@ impl InputColumn {
         if c.is_digit(10) {
             self.chars.push(c);
             self.kind = InputColumnKind::Number
-        } else if c == '+' || c == '*' {
+        } else if c == '+' || c == '*' || c == '-' || c == '/' {
             self.op_char = c;
             if std::mem::discriminant(&self.kind)
                 == std::mem::discriminant(&InputColumnKind::Number)
@@ -66,15 +283,38 @@ impl InputColumn {
         }
     }
 
-    fn get_value(&self) -> Option<i64> {
+    // Returns true if add_token would accept the token: a digit,
+    // blank, or an operator below a digit.
+    //
+    fn accepts(&self, token: &char) -> bool {
+        let c: char = *token;
+        if c.is_ascii_digit() || c == ' ' || c == '\t' {
+            true
+        } else if c == '+' || c == '*' || c == '-' || c == '/' {
+            matches!(self.kind, InputColumnKind::Number)
+        } else {
+            false
+        }
+    }
+
+    // Get the number formed by the column's digits, read top to
+    // bottom. The column index is the column's zero-based position
+    // from the left, used to identify it if the number overflows.
+    //
+    fn get_value(
+        &self,
+        column_idx: u64,
+    ) -> Result<Option<i64>, CephMathError> {
         match self.kind {
-            InputColumnKind::Empty => None,
+            InputColumnKind::Empty => Ok(None),
             InputColumnKind::Number
             | InputColumnKind::NumberAndOperation => {
                 let s = self.chars.iter().cloned().collect::<String>();
                 let s = s.trim();
-                let v: i64 = s.parse::<i64>().unwrap();
-                Some(v)
+                let v: i64 = s.parse::<i64>().map_err(|_| {
+                    CephMathError::ValueOverflow(column_idx + 1)
+                })?;
+                Ok(Some(v))
             }
         }
     }
@@ -85,6 +325,8 @@ impl InputColumn {
             InputColumnKind::NumberAndOperation => match self.op_char {
                 '+' => Some(CephMathOperation::Add),
                 '*' => Some(CephMathOperation::Multiply),
+                '-' => Some(CephMathOperation::Subtract),
+                '/' => Some(CephMathOperation::Divide),
                 _ => {
                     panic!("Column has invalid operation");
                 }
@@ -115,12 +357,63 @@ impl InputColumns {
             idx += 1;
         }
     }
+
+    // As add_columns, but returns an error rather than panicking
+    // if a character cannot go in its column. The line is only
+    // added if every character can.
+    //
+    fn try_add_columns(
+        &mut self,
+        line: &str,
+    ) -> Result<(), CephMathError> {
+        let empty = InputColumn::new();
+        for (idx, c) in line.chars().enumerate() {
+            let idx = idx as u64;
+            let column = self.columns.get(&idx).unwrap_or(&empty);
+            if !column.accepts(&c) {
+                return Err(CephMathError::UnexpectedCharacter(
+                    idx + 1,
+                    c,
+                ));
+            }
+        }
+        self.add_columns(line);
+        Ok(())
+    }
+
+    // Describe each column by its zero-based index, e.g.
+    // (0, "NumberAndOperation 1 *"), (1, "Number 24"), (3, "Empty").
+    // The number is shown as its digits, so a number too large to
+    // parse is still shown.
+    //
+    fn describe(&self) -> Vec<(u64, String)> {
+        let mut descriptions: Vec<(u64, String)> = Vec::new();
+        for (idx, ic) in self.columns.iter() {
+            let digits: String = ic.chars.iter().collect();
+            let description = match ic.kind {
+                InputColumnKind::Empty => format!("{:?}", ic.kind),
+                InputColumnKind::Number => {
+                    format!("{:?} {}", ic.kind, digits)
+                }
+                InputColumnKind::NumberAndOperation => {
+                    format!("{:?} {} {}", ic.kind, digits, ic.op_char)
+                }
+            };
+            descriptions.push((*idx, description));
+        }
+        descriptions
+    }
 }
 
+// A problem's terms and the operations combining them.
+// operations[i], for i from 1, sits between terms[i - 1] and
+// terms[i]; operations[0] is the operation of the problem as a
+// whole. A problem given a single operation has it in every slot.
+//
 #[derive(Debug)]
 struct CephMathProblem {
-    operation: CephMathOperation,
     terms: Vec<i64>,
+    operations: Vec<CephMathOperation>,
     solution: i64,
 }
 
@@ -128,49 +421,160 @@ impl CephMathProblem {
     fn new() -> Self {
         let terms: Vec<i64> = Vec::new();
         Self {
-            operation: CephMathOperation::Unknown,
             terms: terms,
+            operations: Vec::new(),
             solution: 0,
         }
     }
 
     fn add_term(&mut self, term: i64) {
+        self.add_term_with_operation(CephMathOperation::Unknown, term);
+    }
+
+    // add a term, combined with the term before it by the given
+    // operation; for the first term, the operation is the
+    // problem's
+    //
+    fn add_term_with_operation(
+        &mut self,
+        operation: CephMathOperation,
+        term: i64,
+    ) {
         self.terms.push(term);
+        self.operations.push(operation);
     }
 
+    // Give the problem a single operation, filling every slot that
+    // does not already have one
+    //
     fn set_operation(&mut self, operation: CephMathOperation) {
-        self.operation = operation;
+        for slot in self.operations.iter_mut() {
+            if matches!(slot, CephMathOperation::Unknown) {
+                *slot = operation;
+            }
+        }
+    }
+
+    // the operation of the problem as a whole
+    //
+    fn operation(&self) -> CephMathOperation {
+        match self.operations.first() {
+            Some(operation) => *operation,
+            None => CephMathOperation::Unknown,
+        }
+    }
+
+    // true if every pair of terms has a known operation between
+    // them
+    //
+    fn has_operations(&self) -> bool {
+        self.operations
+            .iter()
+            .skip(1)
+            .all(|op| !matches!(op, CephMathOperation::Unknown))
+    }
+
+    // true if the problem has an operation but fewer than two
+    // terms for it to combine. A lone number with no operation
+    // is fine; it solves to itself.
+    //
+    fn lacks_operand(&self) -> bool {
+        (self.terms.len() < 2)
+            && !matches!(self.operation(), CephMathOperation::Unknown)
+    }
+
+    // The name of the problem's operation, e.g. "add", or "mixed"
+    // if the terms are combined by more than one operation
+    //
+    fn operation_name(&self) -> &'static str {
+        let name = self.operation().name();
+        if self.operations.iter().skip(1).all(|op| op.name() == name) {
+            name
+        } else {
+            "mixed"
+        }
     }
 
-    fn solve(&mut self) -> i64 {
-        let mut first = true;
+    // Evaluate the terms as an expression, multiplying and dividing
+    // before adding and subtracting. Each run of * and / is folded
+    // into a single product, then the products are summed.
+    //
+    // Returns an error if a term divides by zero or a step
+    // overflows.
+    //
+    fn evaluate_with_precedence(&self) -> Result<i64, ArithmeticError> {
         let mut result: i64 = 0;
-        for term in self.terms.iter() {
-            if first {
-                result = *term;
-                first = false;
-            } else {
-                match self.operation {
-                    CephMathOperation::Add => {
-                        result += *term;
-                    }
-                    CephMathOperation::Multiply => {
-                        result *= *term;
+        let mut pending = CephMathOperation::Add;
+        let mut product: i64 = match self.terms.first() {
+            Some(term) => *term,
+            None => return Ok(0),
+        };
+        for (idx, term) in self.terms.iter().enumerate().skip(1) {
+            let operation = self.operations[idx];
+            match operation {
+                CephMathOperation::Multiply
+                | CephMathOperation::Divide => {
+                    product = operation.apply(product, *term)?;
+                }
+                CephMathOperation::Add
+                | CephMathOperation::Subtract => {
+                    result = pending.apply(result, product)?;
+                    pending = operation;
+                    product = *term;
+                }
+                CephMathOperation::Unknown => {
+                    panic!("UNKNOWN OPERATION");
+                }
+            }
+        }
+        pending.apply(result, product)
+    }
+
+    // Solve the problem, combining its terms in the given order.
+    //
+    // Returns an error if a term divides by zero or a step
+    // overflows.
+    //
+    fn solve(
+        &mut self,
+        order: FoldOrder,
+    ) -> Result<i64, ArithmeticError> {
+        let mut result: i64 = 0;
+        match order {
+            FoldOrder::LeftToRight => {
+                for (idx, term) in self.terms.iter().enumerate() {
+                    if 0 == idx {
+                        result = *term;
+                    } else {
+                        result = self.operations[idx]
+                            .apply(result, *term)?;
                     }
-                    CephMathOperation::Unknown => {
-                        panic!("UNKNOWN OPERATION");
+                }
+            }
+            FoldOrder::RightToLeft => {
+                for (idx, term) in self.terms.iter().enumerate().rev() {
+                    if (idx + 1) == self.terms.len() {
+                        result = *term;
+                    } else {
+                        result = self.operations[idx + 1]
+                            .apply(*term, result)?;
                     }
                 }
             }
+            FoldOrder::Precedence => {
+                result = self.evaluate_with_precedence()?;
+            }
         }
         self.solution = result;
-        self.solution
+        Ok(self.solution)
     }
 }
 
 #[derive(Debug)]
 struct CephMathProblemSet {
     problems: BTreeMap<u64, CephMathProblem>,
+    fold_order: FoldOrder,
+    direction: ColumnDirection,
 }
 
 impl CephMathProblemSet {
@@ -178,39 +582,92 @@ impl CephMathProblemSet {
     //
     fn new() -> Self {
         let problems: BTreeMap<u64, CephMathProblem> = BTreeMap::new();
-        CephMathProblemSet { problems: problems }
+        CephMathProblemSet {
+            problems: problems,
+            fold_order: FoldOrder::LeftToRight,
+            direction: ColumnDirection::RightToLeft,
+        }
     }
 
-    fn add_columns(&mut self, ics: &InputColumns) {
-        let mut idx: u64 = 0;
+    // set the order in which each problem's terms are combined
+    //
+    fn set_fold_order(&mut self, order: FoldOrder) {
+        self.fold_order = order;
+    }
+
+    // set the order in which add_columns reads the columns
+    //
+    fn set_column_direction(&mut self, direction: ColumnDirection) {
+        self.direction = direction;
+    }
+
+    // Build the problems from the columns, in the column direction
+    // set before calling this (right to left unless set). An
+    // operator gives the whole problem its operation. Whichever
+    // the direction, the problems are indexed right to left.
+    //
+    // Under the Precedence fold order, also set before calling
+    // this, an operator instead combines its column's number with
+    // the number in the column read before it, so a problem may
+    // mix operators. An operator under a problem's first column
+    // read is the operation for any pair of numbers without one.
+    //
+    // Returns an error if a column's number does not fit in an i64.
+    //
+    fn add_columns(
+        &mut self,
+        ics: &InputColumns,
+    ) -> Result<(), CephMathError> {
+        let mut problems: Vec<CephMathProblem> = Vec::new();
         let mut current_problem: CephMathProblem =
             CephMathProblem::new();
-        for kv in ics.columns.iter().rev() {
-            let (_, ic): (&u64, &InputColumn) = kv;
+        let columns: Vec<(&u64, &InputColumn)> = match self.direction {
+            ColumnDirection::RightToLeft => {
+                ics.columns.iter().rev().collect()
+            }
+            ColumnDirection::LeftToRight => {
+                ics.columns.iter().collect()
+            }
+        };
+        for kv in columns {
+            let (column_idx, ic): (&u64, &InputColumn) = kv;
             match ic.kind {
                 InputColumnKind::Empty => {
                     if current_problem.terms.len() != 0 {
-                        self.problems.insert(idx, current_problem);
-                        idx += 1;
+                        current_problem
+                            .set_operation(current_problem.operation());
+                        problems.push(current_problem);
                         current_problem = CephMathProblem::new();
                     }
                 }
                 InputColumnKind::Number => {
-                    let v: i64 = ic.get_value().unwrap();
+                    let v: i64 = ic.get_value(*column_idx)?.unwrap();
                     current_problem.add_term(v);
                 }
                 InputColumnKind::NumberAndOperation => {
-                    let v: i64 = ic.get_value().unwrap();
+                    let v: i64 = ic.get_value(*column_idx)?.unwrap();
                     let op: CephMathOperation =
                         ic.get_operation().unwrap();
-                    current_problem.add_term(v);
-                    current_problem.set_operation(op);
+                    if FoldOrder::Precedence == self.fold_order {
+                        current_problem.add_term_with_operation(op, v);
+                    } else {
+                        current_problem.add_term(v);
+                        current_problem.set_operation(op);
+                    }
                 }
             }
         }
         if current_problem.terms.len() != 0 {
-            self.problems.insert(idx, current_problem);
+            current_problem.set_operation(current_problem.operation());
+            problems.push(current_problem);
         }
+        if ColumnDirection::LeftToRight == self.direction {
+            problems.reverse();
+        }
+        for (idx, problem) in problems.into_iter().enumerate() {
+            self.problems.insert(idx as u64, problem);
+        }
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -241,12 +698,22 @@ impl CephMathProblemSet {
         }
     }
 
-    #[allow(dead_code)]
-    fn add_operations(&mut self, operations: &Vec<&str>) {
-        if operations.len() != self.problems.len() {
-            panic!(
-                "Number of operations does not match number of existing problems."
-            );
+    // Set each problem's operation, from operators listed in the
+    // order the problems are indexed, i.e. right to left.
+    //
+    // Returns an error if there is not one operator per problem,
+    // or an operator is not one of + - * /.
+    //
+    fn add_operations(
+        &mut self,
+        operations: &Vec<&str>,
+    ) -> Result<(), CephMathError> {
+        let count = operations.len();
+        if count != self.problems.len() {
+            return Err(CephMathError::OperatorCount(
+                count,
+                self.problems.len(),
+            ));
         }
         let mut idx: u64 = 0;
         for operation in operations {
@@ -255,11 +722,59 @@ impl CephMathProblemSet {
                 problem.set_operation(CephMathOperation::Multiply);
             } else if operation.eq(&"+") {
                 problem.set_operation(CephMathOperation::Add);
+            } else if operation.eq(&"-") {
+                problem.set_operation(CephMathOperation::Subtract);
+            } else if operation.eq(&"/") {
+                problem.set_operation(CephMathOperation::Divide);
             } else {
-                panic!("INVALID OPERATION");
+                return Err(CephMathError::UnknownOperator(
+                    count - idx as usize,
+                    operation.to_string(),
+                ));
             }
             idx += 1;
         }
+        Ok(())
+    }
+
+    // Apply a row of operators, one per problem, listed in left to
+    // right order. Problems built by add_columns are indexed right
+    // to left, so the operators are applied in reverse.
+    //
+    // Returns an error if there is not one operator per problem,
+    // or an operator is not one of + - * /.
+    //
+    fn add_operator_row(
+        &mut self,
+        line: &str,
+    ) -> Result<(), CephMathError> {
+        let mut operations: Vec<&str> =
+            line.split_whitespace().collect();
+        operations.reverse();
+        self.add_operations(&operations)
+    }
+
+    // Check that every problem has an operation. An operator line
+    // shorter than the number lines can leave the rightmost
+    // problems without one. Also check that every problem with an
+    // operation has at least two numbers for it to combine.
+    //
+    fn check_operations(&self) -> Result<(), CephMathError> {
+        let count = self.problems.len();
+        // problems are indexed right to left, so report the first
+        // one missing an operation from the left
+        //
+        for (idx, problem) in self.problems.values().enumerate().rev() {
+            if !problem.has_operations() {
+                return Err(CephMathError::MissingOperation(
+                    count - idx,
+                ));
+            }
+            if problem.lacks_operand() {
+                return Err(CephMathError::MissingOperand(count - idx));
+            }
+        }
+        Ok(())
     }
 
     fn get_solutions(&self) -> Vec<i64> {
@@ -272,13 +787,240 @@ impl CephMathProblemSet {
         solutions
     }
 
-    fn solve_all(&mut self) {
-        let keys: Vec<u64> = self.problems.keys().cloned().collect();
-        for key in keys {
-            let problem = self.problems.get_mut(&key).unwrap();
-            problem.solve();
+    // The total of the solutions of each kind of problem, keyed by
+    // the name of the problem's operation, e.g. "add". Problems
+    // given an operation between each pair of terms are totalled
+    // under "mixed". The problems must already be solved.
+    //
+    // Will panic if a total overflows.
+    //
+    #[allow(dead_code)]
+    fn totals_by_operation(&self) -> BTreeMap<String, i64> {
+        let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+        for problem in self.problems.values() {
+            let name = problem.operation_name();
+            let total = totals.entry(name.to_string()).or_insert(0);
+            *total = total
+                .checked_add(problem.solution)
+                .expect("total of problem answers overflowed");
+        }
+        totals
+    }
+
+    // Solve all the problems, returning the solutions and their
+    // total.
+    //
+    // Returns an error if a problem divides by zero, or its answer
+    // or the total overflows.
+    //
+    fn solve_and_total(
+        &mut self,
+    ) -> Result<(Vec<i64>, i64), CephMathError> {
+        self.solve_all()?;
+        let solutions = self.get_solutions();
+        let mut total: i64 = 0;
+        for solution in solutions.iter() {
+            total = total
+                .checked_add(*solution)
+                .ok_or(CephMathError::TotalOverflow)?;
+        }
+        Ok((solutions, total))
+    }
+
+    // Solve every problem.
+    //
+    // Returns an error naming the first problem, from the left,
+    // that divides by zero or whose answer overflows.
+    //
+    fn solve_all(&mut self) -> Result<(), CephMathError> {
+        let count = self.problems.len();
+        // problems are indexed right to left
+        //
+        for (idx, problem) in
+            self.problems.values_mut().enumerate().rev()
+        {
+            let position = count - idx;
+            problem.solve(self.fold_order).map_err(|e| match e {
+                ArithmeticError::DivideByZero => {
+                    CephMathError::DivideByZero(position)
+                }
+                ArithmeticError::Overflow => {
+                    CephMathError::AnswerOverflow(position)
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// How many problem answers are negative, zero and positive
+//
+#[derive(Debug, Default, PartialEq)]
+struct SignCounts {
+    negative: usize,
+    zero: usize,
+    positive: usize,
+}
+
+impl SignCounts {
+    fn from_solutions(solutions: &[i64]) -> Self {
+        let mut counts = SignCounts::default();
+        for solution in solutions {
+            match solution.signum() {
+                -1 => counts.negative += 1,
+                0 => counts.zero += 1,
+                _ => counts.positive += 1,
+            }
+        }
+        counts
+    }
+}
+
+// Format an answer with its sign, e.g. "+12" or "-3"; zero has
+// no sign.
+//
+fn format_signed(value: i64) -> String {
+    if 0 == value {
+        "0".to_string()
+    } else {
+        format!("{:+}", value)
+    }
+}
+
+// Print each answer, numbering the problems from the left, then
+// how many are negative, zero and positive. The solutions are
+// indexed right to left, as the problems are.
+//
+fn print_sign_report(solutions: &[i64]) {
+    for (n, solution) in solutions.iter().rev().enumerate() {
+        println!("problem {}: {}", n + 1, format_signed(*solution));
+    }
+    let counts = SignCounts::from_solutions(solutions);
+    println!(
+        "{} negative, {} zero and {} positive answers",
+        counts.negative, counts.zero, counts.positive
+    );
+}
+
+// Transpose the character grid, so row i of the result is made of
+// column i of the rows. Ragged rows are first padded with spaces
+// to the length of the longest row.
+//
+fn transpose_rows(rows: &[String]) -> Vec<String> {
+    let grid: Vec<Vec<char>> =
+        rows.iter().map(|row| row.chars().collect()).collect();
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut transposed: Vec<String> = Vec::new();
+    for col in 0..width {
+        let t_row: String = grid
+            .iter()
+            .map(|row| *row.get(col).unwrap_or(&' '))
+            .collect();
+        transposed.push(t_row);
+    }
+    transposed
+}
+
+// Parse the rows, transposed first if asked, into columns. Blank
+// rows separate the problems of transposed input, so they are only
+// dropped after transposing.
+//
+// Returns an error if a character cannot go in its column.
+//
+fn columns_from_rows(
+    raw_rows: &[String],
+    transpose: bool,
+) -> Result<InputColumns, CephMathError> {
+    let rows: Vec<String> = if transpose {
+        transpose_rows(raw_rows)
+    } else {
+        raw_rows.to_vec()
+    };
+    let mut ics = InputColumns::new();
+    for row in rows.iter() {
+        if row.trim().is_empty() {
+            continue;
         }
+        ics.try_add_columns(row)?;
+    }
+    Ok(ics)
+}
+
+// How read_problems reads the problems from the rows
+//
+#[derive(Debug, Clone, Copy)]
+struct ReadOptions {
+    // transpose the rows before reading them
+    transpose: bool,
+    // the last non-blank row holds one operator per problem
+    operator_row: bool,
+    order: FoldOrder,
+    direction: ColumnDirection,
+}
+
+// Read the rows into columns and the columns into problems, as
+// the options ask.
+//
+// Returns the columns and problems, or an error if a character
+// cannot go in its column, a number does not fit in an i64, the
+// operator row does not give one known operator per problem, or a
+// problem is missing an operator or operand.
+//
+fn read_problems(
+    raw_rows: &[String],
+    options: &ReadOptions,
+) -> Result<(InputColumns, CephMathProblemSet), CephMathError> {
+    let mut rows: Vec<String> = if options.transpose {
+        transpose_rows(raw_rows)
+    } else {
+        raw_rows.to_vec()
+    };
+    rows.retain(|row| !row.trim().is_empty());
+    let operator_row = if options.operator_row {
+        rows.pop()
+    } else {
+        None
+    };
+    let ics = columns_from_rows(&rows, false)?;
+    let mut cmps = CephMathProblemSet::new();
+    cmps.set_fold_order(options.order);
+    cmps.set_column_direction(options.direction);
+    cmps.add_columns(&ics)?;
+    if let Some(row) = operator_row {
+        cmps.add_operator_row(&row)?;
     }
+    cmps.check_operations()?;
+    Ok((ics, cmps))
+}
+
+// Read the problems with the columns right to left, then left to
+// right, keeping the first reading in which every character parses
+// and every problem has an operator between each pair of numbers.
+//
+// Returns the direction read, with the columns and problems; or,
+// if neither direction works, the error from reading right to
+// left.
+//
+fn auto_orient(
+    raw_rows: &[String],
+    order: FoldOrder,
+) -> Result<
+    (ColumnDirection, InputColumns, CephMathProblemSet),
+    CephMathError,
+> {
+    let read = |direction: ColumnDirection| {
+        let options = ReadOptions {
+            transpose: false,
+            operator_row: false,
+            order,
+            direction,
+        };
+        let (ics, cmps) = read_problems(raw_rows, &options)?;
+        Ok((direction, ics, cmps))
+    };
+    read(ColumnDirection::RightToLeft).or_else(|err| {
+        read(ColumnDirection::LeftToRight).map_err(|_| err)
+    })
 }
 
 // Binary crate entry point
@@ -293,22 +1035,46 @@ fn main() -> Result<()> {
     let rdr = BufReader::new(f);
     let lines = rdr.lines();
 
-    let mut grand_total: i64 = 0;
-    let mut cmps = CephMathProblemSet::new();
-    let mut ics = InputColumns::new();
+    let mut raw_rows: Vec<String> = Vec::new();
     for line in lines {
-        let line = line.unwrap();
-        // let line = line.trim();
-        if 0 == line.len() {
-            continue;
+        raw_rows.push(line.unwrap());
+    }
+    let order = if args.fold_right {
+        FoldOrder::RightToLeft
+    } else if args.precedence {
+        FoldOrder::Precedence
+    } else {
+        FoldOrder::LeftToRight
+    };
+    let (ics, mut cmps) = if args.auto_orient {
+        let (direction, ics, cmps) = auto_orient(&raw_rows, order)
+            .with_context(|| {
+                format!(
+                    "No column direction of `{}` parses",
+                    path.display()
+                )
+            })?;
+        println!("Reading the columns {}", direction.describe());
+        (ics, cmps)
+    } else {
+        let options = ReadOptions {
+            transpose: args.transpose,
+            operator_row: args.operator_row,
+            order,
+            direction: ColumnDirection::RightToLeft,
+        };
+        read_problems(&raw_rows, &options).with_context(|| {
+            format!("Invalid problem in `{}`", path.display())
+        })?
+    };
+    if args.describe_columns {
+        for (idx, description) in ics.describe() {
+            println!("column {}: {}", idx, description);
         }
-        ics.add_columns(&line);
     }
-    cmps.add_columns(&ics);
-    cmps.solve_all();
-    let solutions = cmps.get_solutions();
-    for solution in solutions {
-        grand_total += solution;
+    let (solutions, grand_total) = cmps.solve_and_total()?;
+    if args.sign_report {
+        print_sign_report(&solutions);
     }
 
     // Display the grand total of problem answers
@@ -317,6 +1083,26 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+// Read the problems from raw input the way main does by default,
+// taking the last row as an operator row if operator_row.
+//
+#[cfg(test)]
+fn testhelper_read_problems(
+    raw_input: &str,
+    operator_row: bool,
+) -> CephMathProblemSet {
+    let rows: Vec<String> =
+        raw_input.split('\n').map(|x| x.to_string()).collect();
+    let options = ReadOptions {
+        transpose: false,
+        operator_row,
+        order: FoldOrder::LeftToRight,
+        direction: ColumnDirection::RightToLeft,
+    };
+    let (_, cmps) = read_problems(&rows, &options).unwrap();
+    cmps
+}
+
 // test with example input
 //
 #[test]
@@ -338,12 +1124,570 @@ fn given_example() {
         }
         ics.add_columns(line);
     }
-    cmps.add_columns(&ics);
-    cmps.solve_all();
-    let solutions = cmps.get_solutions();
-    let mut actual: i64 = 0;
-    for solution in solutions {
-        actual += solution;
-    }
+    cmps.add_columns(&ics).unwrap();
+    let (_, actual) = cmps.solve_and_total().unwrap();
     assert_eq!(expected, actual);
 }
+
+// test with a separate operator row that is not aligned with
+// the number columns
+//
+#[test]
+fn separate_operator_row() {
+    let expected: i64 = 3263827;
+    let raw_input = "123 328  51 64
+ 45 64  387 23
+  6 98  215 314
+* + * +";
+    let mut cmps = testhelper_read_problems(raw_input, true);
+
+    // problems are indexed right to left
+    //
+    let operations: Vec<CephMathOperation> =
+        cmps.problems.values().map(|p| p.operation()).collect();
+    assert_eq!(4, operations.len());
+    assert!(matches!(operations[0], CephMathOperation::Add));
+    assert!(matches!(operations[1], CephMathOperation::Multiply));
+    assert!(matches!(operations[2], CephMathOperation::Add));
+    assert!(matches!(operations[3], CephMathOperation::Multiply));
+
+    let (_, actual) = cmps.solve_and_total().unwrap();
+    assert_eq!(expected, actual);
+}
+
+// test operator rows that do not give one known operator per
+// problem
+//
+#[test]
+fn bad_operator_row() {
+    let options = ReadOptions {
+        transpose: false,
+        operator_row: true,
+        order: FoldOrder::LeftToRight,
+        direction: ColumnDirection::RightToLeft,
+    };
+    let rows = |operator_row: &str| -> Vec<String> {
+        ["123 328  51 64", " 45 64  387 23", operator_row]
+            .iter()
+            .map(|x| x.to_string())
+            .collect()
+    };
+    assert_eq!(
+        Some(CephMathError::OperatorCount(2, 4)),
+        read_problems(&rows("+ *"), &options).err()
+    );
+    assert_eq!(
+        Some(CephMathError::OperatorCount(5, 4)),
+        read_problems(&rows("+ * + * +"), &options).err()
+    );
+    let err = read_problems(&rows("* + x +"), &options).err();
+    assert_eq!(
+        Some(CephMathError::UnknownOperator(3, "x".to_string())),
+        err
+    );
+    assert_eq!(
+        "Operator 3 (counting from the left), 'x', is not one of + - * /",
+        err.unwrap().to_string()
+    );
+    assert!(read_problems(&rows("* + * +"), &options).is_ok());
+}
+
+// test the solutions and total with example input
+//
+#[test]
+fn given_example_solutions_and_total() {
+    let raw_input = "123 328  51 64
+ 45 64  387 23
+  6 98  215 314
+*   +   *   +  ";
+    let mut cmps = testhelper_read_problems(raw_input, false);
+
+    // problems are indexed right to left
+    //
+    let expected: (Vec<i64>, i64) =
+        (vec![1058, 3253600, 625, 8544], 3263827);
+    assert_eq!(Ok(expected), cmps.solve_and_total());
+}
+
+// test the totals by operation with example input
+//
+#[test]
+fn given_example_totals_by_operation() {
+    let raw_input = "123 328  51 64
+ 45 64  387 23
+  6 98  215 314
+*   +   *   +  ";
+    let mut cmps = testhelper_read_problems(raw_input, false);
+    cmps.solve_and_total().unwrap();
+
+    // the add problems give 1058 + 625, the multiply problems
+    // 3253600 + 8544
+    //
+    let totals = cmps.totals_by_operation();
+    let expected_totals: BTreeMap<String, i64> = BTreeMap::from([
+        ("add".to_string(), 1683),
+        ("multiply".to_string(), 3262144),
+    ]);
+    assert_eq!(expected_totals, totals);
+    assert_eq!(3263827, totals.values().sum::<i64>());
+}
+
+// test counting negative, zero and positive answers
+//
+#[test]
+fn sign_counts_mixed() {
+    // from the left: 1 - 9, 5 - 5, 9 - 4 - 3 and 2 - 1
+    //
+    let raw_input = "91 55 349 12
+-  -  -   - ";
+    let mut cmps = CephMathProblemSet::new();
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    cmps.add_columns(&ics).unwrap();
+    let (solutions, total) = cmps.solve_and_total().unwrap();
+    assert_eq!(vec![1, 2, 0, -8], solutions);
+    assert_eq!(-5, total);
+    assert_eq!(
+        SignCounts {
+            negative: 1,
+            zero: 1,
+            positive: 2
+        },
+        SignCounts::from_solutions(&solutions)
+    );
+    let formatted: Vec<String> =
+        solutions.iter().rev().map(|s| format_signed(*s)).collect();
+    assert_eq!(vec!["-8", "0", "+2", "+1"], formatted);
+}
+
+// test describing the columns of the example input
+//
+#[test]
+fn describe_example_columns() {
+    let raw_input = "123 328  51 64
+ 45 64  387 23
+  6 98  215 314
+*   +   *   +  ";
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    let descriptions = ics.describe();
+    assert_eq!(15, descriptions.len());
+    let expected: Vec<(u64, String)> = vec![
+        (0, "NumberAndOperation 1 *".to_string()),
+        (1, "Number 24".to_string()),
+        (2, "Number 356".to_string()),
+        (3, "Empty".to_string()),
+        (4, "NumberAndOperation 369 +".to_string()),
+    ];
+    assert_eq!(expected, descriptions[0..5].to_vec());
+    assert_eq!((14, "Number 4".to_string()), descriptions[14]);
+}
+
+// test operator lines shorter than the number lines
+//
+#[test]
+fn short_operator_line() {
+    // without its trailing spaces the operator line is shorter,
+    // but every operator is still under its problem
+    //
+    let raw_input = "123 328  51 64
+ 45 64  387 23
+  6 98  215 314
+*   +   *   +";
+    let mut cmps = CephMathProblemSet::new();
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(Ok(()), cmps.check_operations());
+    let (_, actual) = cmps.solve_and_total().unwrap();
+    assert_eq!(3263827, actual);
+
+    // an operator line that stops before the last problem
+    //
+    let raw_input = "123 328  51 64
+ 45 64  387 23
+  6 98  215 314
+*   +   *";
+    let mut cmps = CephMathProblemSet::new();
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(
+        Err(CephMathError::MissingOperation(4)),
+        cmps.check_operations()
+    );
+}
+
+// test that a lone number solves to itself, but a lone number
+// with an operator is reported
+//
+#[test]
+fn single_term_problems() {
+    // the middle problem is a lone number with no operator
+    //
+    let raw_input = "12 7 34
+ 5   6
+*    +";
+    let mut cmps = CephMathProblemSet::new();
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(Ok(()), cmps.check_operations());
+    // problems are indexed right to left: 36 + 4, 7, 1 * 25
+    //
+    assert_eq!(Ok((vec![40, 7, 25], 72)), cmps.solve_and_total());
+
+    // the middle problem is a lone number with an operator
+    //
+    let raw_input = "12 7 34
+ 5   6
+*  - +";
+    let mut cmps = CephMathProblemSet::new();
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(
+        Err(CephMathError::MissingOperand(2)),
+        cmps.check_operations()
+    );
+}
+
+// test that a three term subtraction depends on the fold order
+//
+#[test]
+fn subtraction_fold_order() {
+    let mut problem = CephMathProblem::new();
+    problem.add_term(10);
+    problem.add_term(4);
+    problem.add_term(3);
+    problem.set_operation(CephMathOperation::Subtract);
+    assert_eq!(Ok(3), problem.solve(FoldOrder::LeftToRight));
+    assert_eq!(Ok(9), problem.solve(FoldOrder::RightToLeft));
+
+    // the columns are read right to left, so the terms are 9, 4, 3
+    //
+    let raw_input = "349
+-  ";
+    for (order, expected) in
+        [(FoldOrder::LeftToRight, 2), (FoldOrder::RightToLeft, 8)]
+    {
+        let mut cmps = CephMathProblemSet::new();
+        let mut ics = InputColumns::new();
+        for line in raw_input.split('\n') {
+            ics.add_columns(line);
+        }
+        cmps.add_columns(&ics).unwrap();
+        cmps.set_fold_order(order);
+        assert_eq!(
+            Ok((vec![expected], expected)),
+            cmps.solve_and_total()
+        );
+    }
+}
+
+// test that a problem mixing operators is evaluated with * and /
+// before + and -
+//
+#[test]
+fn precedence_mixed_operators() {
+    // 2 + 3 * 4 - 10 / 5 * 3
+    //
+    let mut problem = CephMathProblem::new();
+    problem.add_term(2);
+    problem.add_term_with_operation(CephMathOperation::Add, 3);
+    problem.add_term_with_operation(CephMathOperation::Multiply, 4);
+    problem.add_term_with_operation(CephMathOperation::Subtract, 10);
+    problem.add_term_with_operation(CephMathOperation::Divide, 5);
+    problem.add_term_with_operation(CephMathOperation::Multiply, 3);
+    assert!(problem.has_operations());
+    assert_eq!(Ok(8), problem.solve(FoldOrder::Precedence));
+
+    // with a single operation, precedence matches the left fold
+    //
+    let mut problem = CephMathProblem::new();
+    problem.add_term(10);
+    problem.add_term(4);
+    problem.add_term(3);
+    problem.set_operation(CephMathOperation::Subtract);
+    assert_eq!(Ok(3), problem.solve(FoldOrder::Precedence));
+
+    // a gap with no operation is reported as missing
+    //
+    let mut cmps = CephMathProblemSet::new();
+    let mut problem = CephMathProblem::new();
+    problem.add_term(2);
+    problem.add_term(3);
+    problem.add_term_with_operation(CephMathOperation::Add, 4);
+    cmps.problems.insert(0, problem);
+    assert_eq!(
+        Err(CephMathError::MissingOperation(1)),
+        cmps.check_operations()
+    );
+
+    // the problem's operation fills the gap: 2 * 3 + 4
+    //
+    cmps.problems
+        .get_mut(&0)
+        .unwrap()
+        .set_operation(CephMathOperation::Multiply);
+    assert_eq!(Ok(()), cmps.check_operations());
+    cmps.set_fold_order(FoldOrder::Precedence);
+    assert_eq!(Ok((vec![10], 10)), cmps.solve_and_total());
+}
+
+// test reading an operator under each number for precedence
+//
+#[test]
+fn precedence_from_columns() {
+    // read right to left: 4 + 3 * 2, and 5 - 4 - 3 * 2 with the
+    // rightmost operator filling the gaps
+    //
+    let raw_input = "234 2345
+*+  *  -";
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    let mut cmps = CephMathProblemSet::new();
+    cmps.set_fold_order(FoldOrder::Precedence);
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(Ok(()), cmps.check_operations());
+    assert_eq!(Ok((vec![-5, 10], 5)), cmps.solve_and_total());
+    assert_eq!(
+        BTreeMap::from([("mixed".to_string(), 5)]),
+        cmps.totals_by_operation()
+    );
+
+    // an operator under the rightmost number applies throughout,
+    // but the puzzle's, under the leftmost, leaves gaps
+    //
+    let raw_input = "123
+  *";
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    let mut cmps = CephMathProblemSet::new();
+    cmps.set_fold_order(FoldOrder::Precedence);
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(Ok((vec![6], 6)), cmps.solve_and_total());
+    let raw_input = "123
+*  ";
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    let mut cmps = CephMathProblemSet::new();
+    cmps.set_fold_order(FoldOrder::Precedence);
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(
+        Err(CephMathError::MissingOperation(1)),
+        cmps.check_operations()
+    );
+}
+
+// test that a grand total too large for an i64 is reported, not
+// a panic
+//
+#[test]
+fn total_overflow() {
+    let mut cmps = CephMathProblemSet::new();
+    for (idx, term) in [i64::MAX - 1, i64::MAX - 2].iter().enumerate() {
+        let mut problem = CephMathProblem::new();
+        problem.add_term(*term);
+        problem.add_term(0);
+        problem.set_operation(CephMathOperation::Add);
+        cmps.problems.insert(idx as u64, problem);
+    }
+    assert_eq!(
+        Err(CephMathError::TotalOverflow),
+        cmps.solve_and_total()
+    );
+}
+
+// test that dividing by a zero term is reported, not a panic
+//
+#[test]
+fn divide_by_zero() {
+    // from the left: 0 / 8, then 3 / 0
+    //
+    let raw_input = "80 03
+/  / ";
+    let mut ics = InputColumns::new();
+    for line in raw_input.split('\n') {
+        ics.add_columns(line);
+    }
+    let mut cmps = CephMathProblemSet::new();
+    cmps.add_columns(&ics).unwrap();
+    assert_eq!(
+        Err(CephMathError::DivideByZero(2)),
+        cmps.solve_and_total()
+    );
+    assert_eq!(
+        "Problem 2 (counting from the left) divides by zero",
+        CephMathError::DivideByZero(2).to_string()
+    );
+}
+
+// test that an answer too large for an i64 is reported, not a
+// panic or a wrapped answer
+//
+#[test]
+fn answer_overflow() {
+    assert_eq!(
+        Err(ArithmeticError::Overflow),
+        CephMathOperation::Add.apply(i64::MAX, 1)
+    );
+    assert_eq!(
+        Err(ArithmeticError::Overflow),
+        CephMathOperation::Subtract.apply(i64::MIN, 1)
+    );
+    assert_eq!(
+        Err(ArithmeticError::Overflow),
+        CephMathOperation::Multiply.apply(i64::MAX, 2)
+    );
+    assert_eq!(
+        Err(ArithmeticError::Overflow),
+        CephMathOperation::Divide.apply(i64::MIN, -1)
+    );
+    assert_eq!(
+        Err(ArithmeticError::DivideByZero),
+        CephMathOperation::Divide.apply(1, 0)
+    );
+
+    // from the left: 1 + 2, then i64::MAX * 2
+    //
+    let mut cmps = CephMathProblemSet::new();
+    for (idx, (operation, lhs, rhs)) in [
+        (CephMathOperation::Multiply, i64::MAX, 2),
+        (CephMathOperation::Add, 1, 2),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut problem = CephMathProblem::new();
+        problem.add_term(lhs);
+        problem.add_term(rhs);
+        problem.set_operation(operation);
+        cmps.problems.insert(idx as u64, problem);
+    }
+    assert_eq!(
+        Err(CephMathError::AnswerOverflow(2)),
+        cmps.solve_and_total()
+    );
+}
+
+// test that a column too tall for an i64 is reported, not a panic
+//
+#[test]
+fn column_value_overflow() {
+    let mut ics = InputColumns::new();
+    for _ in 0..20 {
+        ics.add_columns(" 9");
+    }
+    ics.add_columns(" +");
+    let mut cmps = CephMathProblemSet::new();
+    assert_eq!(
+        Err(CephMathError::ValueOverflow(2)),
+        cmps.add_columns(&ics)
+    );
+
+    // 19 digits still fit
+    //
+    let mut ics = InputColumns::new();
+    for _ in 0..19 {
+        ics.add_columns("1");
+    }
+    ics.add_columns("+");
+    let mut cmps = CephMathProblemSet::new();
+    assert_eq!(Ok(()), cmps.add_columns(&ics));
+    let (_, actual) = cmps.solve_and_total().unwrap();
+    assert_eq!(1111111111111111111, actual);
+}
+
+// test that auto-orient picks the only column direction that
+// parses
+//
+#[test]
+fn auto_orient_examples() {
+    let given: Vec<String> = [
+        "123 328  51 64",
+        " 45 64  387 23",
+        "  6 98  215 314",
+        "*   +   *   +  ",
+    ]
+    .iter()
+    .map(|x| x.to_string())
+    .collect();
+    let (direction, _, mut cmps) =
+        auto_orient(&given, FoldOrder::LeftToRight).unwrap();
+    assert_eq!(ColumnDirection::RightToLeft, direction);
+    let (_, actual) = cmps.solve_and_total().unwrap();
+    assert_eq!(3263827, actual);
+
+    // with precedence, each operator joins its number to the one
+    // read before it, so read right to left the operators under
+    // the leftmost columns join nothing and the other pairs of
+    // numbers have none
+    //
+    let right_to_left = ReadOptions {
+        transpose: false,
+        operator_row: false,
+        order: FoldOrder::Precedence,
+        direction: ColumnDirection::RightToLeft,
+    };
+    assert_eq!(
+        Some(CephMathError::MissingOperation(1)),
+        read_problems(&given, &right_to_left).err()
+    );
+    let (direction, _, mut cmps) =
+        auto_orient(&given, FoldOrder::Precedence).unwrap();
+    assert_eq!(ColumnDirection::LeftToRight, direction);
+    let (solutions, actual) = cmps.solve_and_total().unwrap();
+    // problems are indexed right to left whichever the direction
+    //
+    assert_eq!(vec![1058, 3253600, 625, 8544], solutions);
+    assert_eq!(3263827, actual);
+
+    // neither direction has an operator
+    //
+    let bare: Vec<String> =
+        vec!["12 3".to_string(), "45 6".to_string()];
+    assert_eq!(
+        Some(CephMathError::MissingOperation(1)),
+        auto_orient(&bare, FoldOrder::LeftToRight).err()
+    );
+}
+
+// test that the transposed example gives the same grand total
+//
+#[test]
+fn transposed_example() {
+    let transposed: Vec<String> = [
+        "1  *", "24", "356", "", "369+", "248", "8", "", " 32*", "581",
+        "175", "", "623+", "431", "  4",
+    ]
+    .iter()
+    .map(|x| x.to_string())
+    .collect();
+    let mut cmps = CephMathProblemSet::new();
+    let mut ics = InputColumns::new();
+    for row in transpose_rows(&transposed) {
+        ics.add_columns(&row);
+    }
+    cmps.add_columns(&ics).unwrap();
+    let (_, actual) = cmps.solve_and_total().unwrap();
+    assert_eq!(3263827, actual);
+}
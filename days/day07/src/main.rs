@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
@@ -11,6 +12,18 @@ use clap::Parser;
 ///
 #[derive(Parser)]
 struct Cli {
+    /// Particle counts that would overflow stop at the largest
+    /// possible count instead
+    #[arg(long = "saturating")]
+    saturating: bool,
+    /// Each splitter splits only the first particle to reach it;
+    /// the rest of the particles pass straight through
+    #[arg(long = "single-use-splitters")]
+    single_use_splitters: bool,
+    /// Print each processed row of equipment and the particle
+    /// counts by column leaving it
+    #[arg(long = "trace")]
+    trace: bool,
     /// The path to the file containing battery bank specs
     path: PathBuf,
 }
@@ -22,6 +35,43 @@ enum Equipment {
     Start,
 }
 
+#[derive(Debug, PartialEq)]
+enum EquipmentConfigError {
+    // the (zero-based) column and the offending character
+    UnknownCharacter(usize, char),
+    // no row has a beam entry point
+    MissingStart,
+    // the (zero-based) row, counting from the first row with an
+    // entry point, of a second entry point
+    ExtraStart(usize),
+}
+
+impl fmt::Display for EquipmentConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquipmentConfigError::UnknownCharacter(column, c) => {
+                write!(
+                    f,
+                    "Unknown equipment character '{}' at column {}",
+                    c, column
+                )
+            }
+            EquipmentConfigError::MissingStart => {
+                write!(f, "No row has a beam entry point")
+            }
+            EquipmentConfigError::ExtraStart(row) => {
+                write!(
+                    f,
+                    "Row {} after the first beam entry point has another",
+                    row
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EquipmentConfigError {}
+
 #[derive(Debug)]
 struct EquipmentConfig {
     config: Vec<Equipment>,
@@ -60,11 +110,17 @@ impl EquipmentConfig {
 
     // parse an input line into a set of equipment
     //
-    fn into_equipment(&mut self, line: &str) {
+    // Returns an error naming the column of the first character
+    // that is not a known kind of equipment.
+    //
+    fn into_equipment(
+        &mut self,
+        line: &str,
+    ) -> Result<(), EquipmentConfigError> {
         if 0 < self.config.len() {
             panic!("already configured; cannot reconfigure");
         }
-        for c in line.chars() {
+        for (column, c) in line.chars().enumerate() {
             match c {
                 '.' => {
                     self.config.push(Equipment::Empty);
@@ -77,9 +133,29 @@ impl EquipmentConfig {
                     self.has_start = true;
                     self.start_idx = self.config.len() - 1;
                 }
-                _ => {}
+                _ => {
+                    return Err(
+                        EquipmentConfigError::UnknownCharacter(
+                            column, c,
+                        ),
+                    );
+                }
             }
         }
+        Ok(())
+    }
+
+    // The equipment as an input line, e.g. "..S.^."
+    //
+    fn render(&self) -> String {
+        self.config
+            .iter()
+            .map(|e| match e {
+                Equipment::Empty => '.',
+                Equipment::Splitter => '^',
+                Equipment::Start => 'S',
+            })
+            .collect()
     }
 
     // length of the equipment list
@@ -101,84 +177,320 @@ impl EquipmentConfig {
     }
 }
 
-// Binary crate entry point
+// How the particles propagate through the equipment
 //
-fn main() -> Result<()> {
-    let args = Cli::parse();
-    let path = &args.path;
+#[derive(Debug, Default, Clone, Copy)]
+struct PropagationOptions {
+    // counts that would overflow stop at u128::MAX
+    saturating: bool,
+    // each splitter splits only one particle; the others pass
+    // straight through as though it were empty space
+    single_use_splitters: bool,
+}
 
-    let f = File::open(path).with_context(|| {
-        format!("Could not open `{}`", path.display())
-    })?;
-    let rdr = BufReader::new(f);
-    let lines = rdr.lines();
+// Add count particles to n. When saturating, a sum that would
+// overflow stops at u128::MAX.
+//
+// Returns true if the sum saturated.
+//
+fn add_particles(n: &mut u128, count: u128, saturating: bool) -> bool {
+    if !saturating {
+        *n += count;
+        return false;
+    }
+    match n.checked_add(count) {
+        Some(sum) => {
+            *n = sum;
+            false
+        }
+        None => {
+            *n = u128::MAX;
+            true
+        }
+    }
+}
 
-    let mut started: bool = false;
-    let mut incoming_particles: BTreeMap<usize, usize> =
-        BTreeMap::new();
-    for line in lines {
-        let line = line.unwrap();
+// The particle totals through one row of equipment. Going
+// straight keeps a particle; a splitter turns it into two, one
+// of which escapes if the splitter is at the edge of the row.
+//
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct RowFlow {
+    // the particles entering the row
+    incoming: u128,
+    // the particles that hit a splitter that fired
+    split: u128,
+    // the particles split off beyond either edge of the row
+    escaped: u128,
+    // the particles leaving the row
+    outgoing: u128,
+}
+
+impl RowFlow {
+    // Returns true if no particle was made or lost other than by
+    // splitting or escaping, i.e.
+    // outgoing == incoming + split - escaped
+    //
+    fn is_conserved(&self) -> bool {
+        self.incoming
+            .checked_add(self.split)
+            .and_then(|n| n.checked_sub(self.escaped))
+            == Some(self.outgoing)
+    }
+}
+
+// Propagate the incoming particles through a row of the
+// equipment. The row's totals are written to flow.
+//
+// Returns the particle count by column leaving the row, and
+// whether any count saturated.
+//
+fn propagate_row(
+    equip: &EquipmentConfig,
+    incoming_particles: &BTreeMap<usize, u128>,
+    options: &PropagationOptions,
+    flow: &mut RowFlow,
+) -> (BTreeMap<usize, u128>, bool) {
+    let mut outgoing_particles: BTreeMap<usize, u128> = BTreeMap::new();
+    let mut saturated: bool = false;
+    let saturating = options.saturating;
+    let equip_count = equip.len();
+    *flow = RowFlow::default();
+    for (key, count) in incoming_particles.iter() {
+        let beam_idx = *key;
+        saturated |=
+            add_particles(&mut flow.incoming, *count, saturating);
+        let mut split_count: u128 = 0;
+        if equip.has_splitter_at(beam_idx) {
+            if !options.single_use_splitters {
+                split_count = *count;
+            } else {
+                // each splitter is reached by one merged beam, so
+                // it fires on one particle; the rest pass straight
+                // through
+                //
+                split_count = u128::min(1, *count);
+            }
+        }
+        let straight_count = *count - split_count;
+        if 0 < split_count {
+            saturated |=
+                add_particles(&mut flow.split, split_count, saturating);
+            if 0 == beam_idx {
+                saturated |= add_particles(
+                    &mut flow.escaped,
+                    split_count,
+                    saturating,
+                );
+            }
+            if beam_idx >= (equip_count - 1) {
+                saturated |= add_particles(
+                    &mut flow.escaped,
+                    split_count,
+                    saturating,
+                );
+            }
+            if beam_idx > 0 {
+                let n =
+                    outgoing_particles.entry(beam_idx - 1).or_insert(0);
+                saturated |= add_particles(n, split_count, saturating);
+            }
+            if beam_idx < (equip_count - 1) {
+                let n =
+                    outgoing_particles.entry(beam_idx + 1).or_insert(0);
+                saturated |= add_particles(n, split_count, saturating);
+            }
+        }
+        if 0 < straight_count {
+            let n = outgoing_particles.entry(beam_idx).or_insert(0);
+            saturated |= add_particles(n, straight_count, saturating);
+        }
+    }
+    for count in outgoing_particles.values() {
+        saturated |=
+            add_particles(&mut flow.outgoing, *count, saturating);
+    }
+    (outgoing_particles, saturated)
+}
+
+// The particle counts by column for each processed row, whether
+// any count saturated along the way, and the columns, across the
+// widest row, that no particle leaves the last row from
+//
+struct Propagation {
+    // the equipment line of each processed row, matching history
+    rows: Vec<String>,
+    history: Vec<BTreeMap<usize, u128>>,
+    // the totals through each row after the entry row, so
+    // flows[i] took history[i] to history[i + 1]
+    #[allow(dead_code)]
+    flows: Vec<RowFlow>,
+    saturated: bool,
+    #[allow(dead_code)]
+    dead_columns: Vec<usize>,
+}
+
+impl Propagation {
+    // The number of paths, i.e. the particles leaving the last
+    // row. When saturating, a total that would overflow stops at
+    // u128::MAX.
+    //
+    // Returns the path count and whether it (or any count before
+    // it) saturated.
+    //
+    fn path_count(&self, saturating: bool) -> (u128, bool) {
+        let mut path_count: u128 = 0;
+        let mut saturated = self.saturated;
+        if let Some(last) = self.history.last() {
+            for count in last.values() {
+                saturated |=
+                    add_particles(&mut path_count, *count, saturating);
+            }
+        }
+        (path_count, saturated)
+    }
+
+    // Write one block per processed row: the equipment line, then
+    // the particle counts by column leaving it, e.g.
+    //
+    //   ......^.^......
+    //     particles: {5: 1, 7: 2, 9: 1}
+    //
+    fn write_trace<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        for (row, particles) in
+            self.rows.iter().zip(self.history.iter())
+        {
+            writeln!(w, "{}", row)?;
+            writeln!(w, "  particles: {:?}", particles)?;
+        }
+        Ok(())
+    }
+}
+
+// Parse every non-blank line of the reader into a row of
+// equipment, so the whole grid can be inspected before the
+// particles are propagated through it.
+//
+// Returns an error if a line cannot be read or contains an unknown
+// equipment character.
+//
+fn parse_grid<R: BufRead>(r: R) -> Result<Vec<EquipmentConfig>> {
+    let mut grid: Vec<EquipmentConfig> = Vec::new();
+    for (line_idx, line) in r.lines().enumerate() {
+        let line = line?;
         let line = line.trim();
-        if 0 == line.len() {
+        if line.is_empty() {
             continue;
         }
-        let mut outgoing_particles: BTreeMap<usize, usize> =
-            BTreeMap::new();
         let mut equip: EquipmentConfig = EquipmentConfig::new();
-        equip.into_equipment(line);
+        equip
+            .into_equipment(line)
+            .with_context(|| format!("Line {}", line_idx + 1))?;
+        grid.push(equip);
+    }
+    Ok(grid)
+}
+
+// Propagate the particles through the rows of equipment, starting
+// at the beam entry point. Rows above the entry point are skipped.
+//
+// Returns the particle count by column for every processed row,
+// beginning with the row containing the entry point, or an error
+// if there is not exactly one entry point.
+//
+fn propagate(
+    grid: &[EquipmentConfig],
+    options: &PropagationOptions,
+) -> Result<Propagation, EquipmentConfigError> {
+    let mut rows: Vec<String> = Vec::new();
+    let mut history: Vec<BTreeMap<usize, u128>> = Vec::new();
+    let mut flows: Vec<RowFlow> = Vec::new();
+    let mut saturated: bool = false;
+    let mut started: bool = false;
+    let mut width: usize = 0;
+    let mut incoming_particles: BTreeMap<usize, u128> = BTreeMap::new();
+    for equip in grid.iter() {
+        width = usize::max(width, equip.len());
         if !started && equip.has_start() {
-            outgoing_particles.insert(equip.start_at(), 1);
+            incoming_particles.insert(equip.start_at(), 1);
             started = true;
         } else if started {
             if equip.has_start() {
-                panic!("multiple beam entry points!");
-            }
-            let equip_count = equip.len();
-            for key in incoming_particles.keys() {
-                let beam_idx = *key;
-                if equip.has_splitter_at(beam_idx) {
-                    if beam_idx > 0 {
-                        let i = beam_idx - 1;
-                        if !outgoing_particles.contains_key(&i) {
-                            outgoing_particles.insert(i, 0);
-                        }
-                        let n: &mut usize =
-                            outgoing_particles.get_mut(&i).unwrap();
-                        *n += incoming_particles[key];
-                    }
-                    if beam_idx < (equip_count - 1) {
-                        let i = beam_idx + 1;
-                        if !outgoing_particles.contains_key(&i) {
-                            outgoing_particles.insert(i, 0);
-                        }
-                        let n: &mut usize =
-                            outgoing_particles.get_mut(&i).unwrap();
-                        *n += incoming_particles[key];
-                    }
-                } else {
-                    let i: usize = *key;
-                    if !outgoing_particles.contains_key(&i) {
-                        outgoing_particles.insert(i, 0);
-                    }
-                    let n: &mut usize =
-                        outgoing_particles.get_mut(&i).unwrap();
-                    *n += incoming_particles[key];
-                }
+                return Err(EquipmentConfigError::ExtraStart(
+                    rows.len(),
+                ));
             }
+            let mut flow = RowFlow::default();
+            let (outgoing_particles, row_saturated) = propagate_row(
+                equip,
+                &incoming_particles,
+                options,
+                &mut flow,
+            );
+            // once saturated, the totals no longer add up
+            //
+            debug_assert!(row_saturated || flow.is_conserved());
+            flows.push(flow);
+            incoming_particles = outgoing_particles;
+            saturated |= row_saturated;
+        } else {
+            continue;
         }
-        incoming_particles = outgoing_particles;
+        rows.push(equip.render());
+        history.push(incoming_particles.clone());
     }
     if !started {
-        panic!("NOT STARTED!!");
+        return Err(EquipmentConfigError::MissingStart);
+    }
+    let dead_columns: Vec<usize> = (0..width)
+        .filter(|col| {
+            0 == incoming_particles.get(col).copied().unwrap_or(0)
+        })
+        .collect();
+    Ok(Propagation {
+        rows,
+        history,
+        flows,
+        saturated,
+        dead_columns,
+    })
+}
+
+// Binary crate entry point
+//
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let path = &args.path;
+
+    let f = File::open(path).with_context(|| {
+        format!("Could not open `{}`", path.display())
+    })?;
+    let grid = parse_grid(BufReader::new(f)).with_context(|| {
+        format!("Invalid equipment in `{}`", path.display())
+    })?;
+    let options = PropagationOptions {
+        saturating: args.saturating,
+        single_use_splitters: args.single_use_splitters,
+    };
+    let propagation =
+        propagate(&grid, &options).with_context(|| {
+            format!("Invalid equipment in `{}`", path.display())
+        })?;
+    if args.trace {
+        propagation.write_trace(&mut std::io::stdout().lock())?;
     }
 
     // Display the grand total of problem answers
     //
-    let mut path_count: usize = 0;
-    for count in incoming_particles.values() {
-        path_count += count;
-    }
+    let (path_count, saturated) =
+        propagation.path_count(args.saturating);
     println!("The path count is {}", path_count);
+    if saturated {
+        println!(
+            "The particle counts saturated; the path count is at least {}",
+            path_count
+        );
+    }
     Ok(())
 }
 
@@ -218,7 +530,7 @@ fn given_example_quantum_fast() {
         let mut outgoing_particles: BTreeMap<usize, usize> =
             BTreeMap::new();
         let mut equip: EquipmentConfig = EquipmentConfig::new();
-        equip.into_equipment(line);
+        equip.into_equipment(line).unwrap();
         if !started && equip.has_start() {
             outgoing_particles.insert(equip.start_at(), 1);
             started = true;
@@ -270,3 +582,297 @@ fn given_example_quantum_fast() {
     }
     assert_eq!(expected_path_count, actual_path_count);
 }
+
+// the quantum example from the puzzle
+//
+#[cfg(test)]
+const QUANTUM_EXAMPLE: &str = ".......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+// test the per-row particle history with example input
+//
+#[test]
+fn given_example_history() {
+    let grid = parse_grid(QUANTUM_EXAMPLE.as_bytes()).unwrap();
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    assert!(!propagation.saturated);
+    let history = propagation.history;
+    assert_eq!(16, history.len());
+    let expected_start: BTreeMap<usize, u128> =
+        BTreeMap::from([(7, 1)]);
+    assert_eq!(expected_start, history[0]);
+    let expected_row2: BTreeMap<usize, u128> =
+        BTreeMap::from([(6, 1), (8, 1)]);
+    assert_eq!(expected_row2, history[2]);
+    let expected_row4: BTreeMap<usize, u128> =
+        BTreeMap::from([(5, 1), (7, 2), (9, 1)]);
+    assert_eq!(expected_row4, history[4]);
+    let expected_row6: BTreeMap<usize, u128> =
+        BTreeMap::from([(4, 1), (6, 3), (8, 3), (10, 1)]);
+    assert_eq!(expected_row6, history[6]);
+    let path_count: u128 = history.last().unwrap().values().sum();
+    assert_eq!(40, path_count);
+}
+
+// test that tracing writes one block per non-blank row
+//
+#[test]
+fn given_example_trace() {
+    let grid = parse_grid(QUANTUM_EXAMPLE.as_bytes()).unwrap();
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    let mut written: Vec<u8> = Vec::new();
+    propagation.write_trace(&mut written).unwrap();
+    let written = String::from_utf8(written).unwrap();
+    let trace: Vec<&str> = written.lines().collect();
+    let lines: Vec<&str> = QUANTUM_EXAMPLE.split('\n').collect();
+    let row_count = lines.iter().filter(|x| !x.is_empty()).count();
+    assert_eq!(16, row_count);
+    assert_eq!(2 * row_count, trace.len());
+    for (block, line) in trace.chunks(2).zip(lines.iter()) {
+        assert_eq!(*line, block[0]);
+        assert!(block[1].starts_with("  particles: {"));
+    }
+    assert_eq!("  particles: {7: 1}", trace[1]);
+    assert_eq!("  particles: {5: 1, 7: 2, 9: 1}", trace[9]);
+}
+
+// test which columns receive no particles with example input
+//
+#[test]
+fn given_example_dead_columns() {
+    let grid = parse_grid(QUANTUM_EXAMPLE.as_bytes()).unwrap();
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    let last = propagation.history.last().unwrap();
+    let expected: Vec<usize> = vec![1, 3, 5, 7, 9, 13];
+    assert_eq!(expected, propagation.dead_columns);
+    for col in expected {
+        assert!(!last.contains_key(&col));
+    }
+    assert_eq!(15 - 6, last.len());
+}
+
+// test that every row conserves particles with example input
+//
+#[test]
+fn given_example_conservation() {
+    let grid = parse_grid(QUANTUM_EXAMPLE.as_bytes()).unwrap();
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    let history = &propagation.history;
+    assert_eq!(history.len() - 1, propagation.flows.len());
+    for (i, flow) in propagation.flows.iter().enumerate() {
+        assert!(flow.is_conserved(), "row {}: {:?}", i + 1, flow);
+        let incoming: u128 = history[i].values().sum();
+        let outgoing: u128 = history[i + 1].values().sum();
+        assert_eq!(incoming, flow.incoming);
+        assert_eq!(outgoing, flow.outgoing);
+        assert_eq!(0, flow.escaped);
+    }
+    // the first splitter turns one particle into two
+    //
+    assert_eq!(
+        RowFlow {
+            incoming: 1,
+            split: 1,
+            escaped: 0,
+            outgoing: 2
+        },
+        propagation.flows[1]
+    );
+
+    // splitters at either edge split particles off beyond it
+    //
+    let grid = parse_grid("S..\n^..\n.^.\n^.^".as_bytes()).unwrap();
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    let expected: Vec<RowFlow> = vec![
+        RowFlow {
+            incoming: 1,
+            split: 1,
+            escaped: 1,
+            outgoing: 1,
+        },
+        RowFlow {
+            incoming: 1,
+            split: 1,
+            escaped: 0,
+            outgoing: 2,
+        },
+        RowFlow {
+            incoming: 2,
+            split: 2,
+            escaped: 2,
+            outgoing: 2,
+        },
+    ];
+    assert_eq!(expected, propagation.flows);
+    assert!(propagation.flows.iter().all(|x| x.is_conserved()));
+}
+
+// test parsing the whole example grid before propagating
+//
+#[test]
+fn given_example_parse_grid() {
+    let grid = parse_grid(QUANTUM_EXAMPLE.as_bytes()).unwrap();
+    assert_eq!(16, grid.len());
+    assert!(grid[0].has_start());
+    assert_eq!(7, grid[0].start_at());
+    assert!(grid[1..].iter().all(|row| !row.has_start()));
+    assert!(grid.iter().all(|row| 15 == row.len()));
+    assert_eq!("......^.^......", grid[4].render());
+
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    assert_eq!((40, false), propagation.path_count(false));
+
+    // a bad character is reported with its line
+    //
+    let err = parse_grid("..S..\n\n..^.#".as_bytes()).err().unwrap();
+    assert_eq!("Line 3", err.to_string());
+    assert_eq!(
+        Some(&EquipmentConfigError::UnknownCharacter(4, '#')),
+        err.downcast_ref::<EquipmentConfigError>()
+    );
+
+    // exactly one entry point is needed
+    //
+    let grid = parse_grid("...\n.^.".as_bytes()).unwrap();
+    assert_eq!(
+        Some(EquipmentConfigError::MissingStart),
+        propagate(&grid, &PropagationOptions::default()).err()
+    );
+    let grid = parse_grid(".S.\n.^.\n.S.".as_bytes()).unwrap();
+    assert_eq!(
+        Some(EquipmentConfigError::ExtraStart(2)),
+        propagate(&grid, &PropagationOptions::default()).err()
+    );
+}
+
+// test that an unknown character is reported with its column
+//
+#[test]
+fn unknown_equipment_character() {
+    let mut equip: EquipmentConfig = EquipmentConfig::new();
+    assert_eq!(
+        Err(EquipmentConfigError::UnknownCharacter(3, 'X')),
+        equip.into_equipment("..^X..")
+    );
+
+    let err =
+        parse_grid("..S..\n.....\n..^.#".as_bytes()).err().unwrap();
+    assert_eq!(
+        Some(&EquipmentConfigError::UnknownCharacter(4, '#')),
+        err.downcast_ref::<EquipmentConfigError>()
+    );
+}
+
+// test that counts too large for u128 saturate
+//
+#[test]
+fn saturating_path_count() {
+    // each row of splitters splits every beam in two, so after
+    // 140 rows the middle columns hold more than u128::MAX paths
+    //
+    let row_count: usize = 140;
+    let width: usize = 2 * row_count + 3;
+    let start: usize = row_count + 1;
+    let mut raw_lines: Vec<String> = Vec::new();
+    let mut start_row: Vec<char> = vec!['.'; width];
+    start_row[start] = 'S';
+    raw_lines.push(start_row.iter().collect());
+    for row in 0..row_count {
+        let splitters: String = (0..width)
+            .map(|col| {
+                if (col + row + start).is_multiple_of(2) {
+                    '^'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        raw_lines.push(splitters);
+    }
+    let grid = parse_grid(raw_lines.join("\n").as_bytes()).unwrap();
+
+    let propagation = propagate(
+        &grid,
+        &PropagationOptions {
+            saturating: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(propagation.saturated);
+    assert_eq!((u128::MAX, true), propagation.path_count(true));
+}
+
+// test that a single use splitter splits one particle of a merged
+// beam and lets the rest pass straight through
+//
+#[test]
+fn single_use_splitters() {
+    let raw_input = "...S...
+...^...
+..^.^..
+...^...";
+    let grid = parse_grid(raw_input.as_bytes()).unwrap();
+
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    assert_eq!((6, false), propagation.path_count(false));
+
+    let options = PropagationOptions {
+        single_use_splitters: true,
+        ..Default::default()
+    };
+    let propagation = propagate(&grid, &options).unwrap();
+    let expected: BTreeMap<usize, u128> =
+        BTreeMap::from([(1, 1), (2, 1), (3, 1), (4, 1), (5, 1)]);
+    assert_eq!(&expected, propagation.history.last().unwrap());
+    assert_eq!((5, false), propagation.path_count(false));
+}
+
+// test that single use splitters stacked in one column each fire
+// once, rather than the column firing once
+//
+#[test]
+fn single_use_splitters_stacked() {
+    let raw_input = "...S...
+...^...
+..^.^..
+...^...
+...^...";
+    let grid = parse_grid(raw_input.as_bytes()).unwrap();
+
+    let propagation =
+        propagate(&grid, &PropagationOptions::default()).unwrap();
+    assert_eq!((6, false), propagation.path_count(false));
+
+    let options = PropagationOptions {
+        single_use_splitters: true,
+        ..Default::default()
+    };
+    let propagation = propagate(&grid, &options).unwrap();
+    let expected: BTreeMap<usize, u128> =
+        BTreeMap::from([(1, 1), (2, 2), (4, 2), (5, 1)]);
+    assert_eq!(&expected, propagation.history.last().unwrap());
+    assert_eq!((6, false), propagation.path_count(false));
+}
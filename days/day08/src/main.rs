@@ -7,8 +7,10 @@ use std::path::PathBuf;
 use std::usize;
 
 use anyhow::{Context, Result};
+#[cfg(test)]
+use aoc_common::{DAY08_EXAMPLE, DAY08_EXAMPLE_SIZES_AFTER_10};
+use aoc_common::{assemble_circuits, parse_point3};
 use clap::{Id, Parser};
-use regex::Regex;
 
 /// Given input file containing the problem set,
 /// establish the circuits and return the product
@@ -73,6 +75,10 @@ impl JunctionBox {
     }
 }
 
+// The circuits of the original search, now only used by the
+// tests to check assemble_circuits
+//
+#[allow(dead_code)]
 #[derive(Debug)]
 struct Circuit<'a> {
     // contains references to junction boxes that make
@@ -81,6 +87,7 @@ struct Circuit<'a> {
     junction_boxes: BTreeMap<usize, &'a JunctionBox>,
 }
 
+#[allow(dead_code)]
 impl<'a> Circuit<'a> {
     // constructor
     //
@@ -391,129 +398,100 @@ fn list_sizes_of_largest_circuits(
     result
 }
 
-fn list_sizes_of_largest_circuits_fast(
+// List the pairs of junction boxes in ascending order by
+// distance; pairs at equal distance are ordered by their box ids.
+//
+fn sort_pairs_by_distance(
+    distance_by_pair: &BTreeMap<String, u64>,
+) -> Vec<(usize, usize)> {
+    let mut pairs: Vec<(u64, usize, usize)> = Vec::new();
+    for (key, dist) in distance_by_pair.iter() {
+        let (id_a, id_b) = produce_pair(key);
+        pairs.push((*dist, id_a, id_b));
+    }
+    pairs.sort();
+    pairs.iter().map(|(_, id_a, id_b)| (*id_a, *id_b)).collect()
+}
+
+// Make the circuits using up to `upto` connections and return the
+// product of the sizes of the `productoflargest` largest circuits.
+//
+fn product_of_largest_circuits(
     upto: usize,
+    productoflargest: usize,
     junction_boxes: &mut Vec<JunctionBox>,
-) -> Vec<usize> {
-    let mut circuits: Vec<Circuit> = Vec::new();
-    let mut circuits_deleted: Vec<bool> = Vec::new();
-    let mut circuits_by_id: BTreeMap<usize, usize> = BTreeMap::new();
-
-    // find the n closest junction boxeds
-    //
+) -> usize {
     let mut distance_by_pair: BTreeMap<String, u64> = BTreeMap::new();
     list_all_pair_distances(junction_boxes, &mut distance_by_pair);
+    let sorted_pairs = sort_pairs_by_distance(&distance_by_pair);
+    let circuit_sizes: Vec<usize> =
+        assemble_circuits(upto, &sorted_pairs, junction_boxes.len());
+    let mut product: usize = 1;
+    let end = usize::min(productoflargest, circuit_sizes.len());
+    for i in 0..end {
+        product *= circuit_sizes[i];
+    }
+    product
+}
 
-    // sort in ascending order by distance
-    //
-    let mut keys: Vec<&String> = distance_by_pair.keys().collect();
-    keys.sort_by(|a, b| {
-        let d_a: u64 = *distance_by_pair.get(a.as_str()).unwrap();
-        let d_b: u64 = *distance_by_pair.get(b.as_str()).unwrap();
-        if d_a > d_b {
-            Ordering::Greater
-        } else if d_a < d_b {
-            Ordering::Less
-        } else {
-            Ordering::Equal
-        }
-    });
+// Parse a line of input into junction box coordinates.
+//
+// Returns None, after reporting the failure, if the line is not
+// a set of x,y,z coordinates.
+//
+fn parse_coords(
+    line_num: usize,
+    line: &str,
+) -> Option<(i64, i64, i64)> {
+    let coords = parse_point3(line);
+    if coords.is_none() {
+        println!(
+            "*** FAILED *** to match line {}: '{}'",
+            line_num, line
+        );
+    }
+    coords
+}
 
-    let mut connection_count: usize = 0;
-    for key in keys {
-        if connection_count >= upto {
-            break;
+fn file_to_junction_boxes(f: File) -> Vec<JunctionBox> {
+    let rdr = BufReader::new(f);
+    let lines = rdr.lines();
+    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
+    let mut line_num: usize = 0;
+    let mut idx: usize = 0;
+    for line in lines {
+        line_num += 1;
+        let line = line.unwrap();
+        let line = line.trim();
+        if 0 == line.len() {
+            continue;
         }
-        let (id_a, id_b): (usize, usize) = produce_pair(key);
-        let a_in_circuit = circuits_by_id.contains_key(&id_a);
-        let b_in_circuit = circuits_by_id.contains_key(&id_b);
-        if a_in_circuit {
-            let cid_a = *circuits_by_id.get(&id_a).unwrap();
-            if b_in_circuit {
-                let cid_b = *circuits_by_id.get(&id_b).unwrap();
-                if cid_a == cid_b {
-                    // both boxes are in the same circuit. Count that
-                    // as a connection.
-                    //
-                    connection_count += 1;
-                } else {
-                    // each box is in a different circuit;
-                    // MERGE the circuits
-                    //
-                    let circuit_b = circuits.get(cid_b).unwrap();
-                    let mut jbs: Vec<usize> = Vec::new();
-                    for jb_id in circuit_b.junction_boxes.keys() {
-                        jbs.push(*jb_id);
-                    }
-                    let circuit_a = circuits.get_mut(cid_a).unwrap();
-                    circuit_a.merge(&junction_boxes, &jbs);
-                    circuits_deleted[cid_b] = true;
-                    connection_count += 1;
-                }
-            } else {
-                let circuit_a = circuits.get_mut(cid_a).unwrap();
-                circuit_a.add(junction_boxes.get(id_b).unwrap());
-                circuits_by_id.insert(id_b, cid_a);
-                connection_count += 1;
-            }
-        } else if b_in_circuit {
-            let cid_b = *circuits_by_id.get(&id_b).unwrap();
-            let circuit_b = circuits.get_mut(cid_b).unwrap();
-            circuit_b.add(junction_boxes.get(id_a).unwrap());
-            circuits_by_id.insert(id_a, cid_b);
-            connection_count += 1;
-        } else {
-            let mut circuit_new: Circuit =
-                Circuit::new(junction_boxes.get(id_a).unwrap());
-            circuit_new.add(junction_boxes.get(id_b).unwrap());
-            circuits.push(circuit_new);
-            circuits_deleted.push(false);
-            let cid_new = circuits.len() - 1;
-            circuits_by_id.insert(id_a, cid_new);
-            circuits_by_id.insert(id_b, cid_new);
-            connection_count += 1;
+        if let Some((x, y, z)) = parse_coords(line_num, line) {
+            junction_boxes.push(JunctionBox::new(x, y, z, idx));
+            idx += 1;
         }
     }
+    junction_boxes
+}
 
-    // sort circuits by size and id
-    //
-    let mut largest_circuits: Vec<usize> = Vec::new();
-    for i in 0..circuits.len() {
-        if !circuits_deleted[i] {
-            largest_circuits.push(i);
-        } else {
-            largest_circuits.push(usize::MAX);
-        }
-    }
-    largest_circuits.sort_by(|a, b| {
-        if *a == usize::MAX && *b == usize::MAX {
-            Ordering::Equal
-        } else if *a == usize::MAX {
-            Ordering::Greater
-        } else if *b == usize::MAX {
-            Ordering::Less
-        } else {
-            let c_a: &Circuit = circuits.get(*a).unwrap();
-            let c_b: &Circuit = circuits.get(*b).unwrap();
-            if c_a.junction_box_count() > c_b.junction_box_count() {
-                Ordering::Less
-            } else if c_a.junction_box_count()
-                < c_b.junction_box_count()
-            {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
-            }
+#[cfg(test)]
+fn string_to_junction_boxes(raw_input: &str) -> Vec<JunctionBox> {
+    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
+    let lines = raw_input.split('\n');
+    let mut line_num: usize = 0;
+    let mut idx: usize = 0;
+    for line in lines {
+        line_num += 1;
+        let line = line.trim();
+        if 0 == line.len() {
+            continue;
         }
-    });
-
-    let mut result: Vec<usize> = Vec::new();
-    for i in largest_circuits.iter() {
-        if *i != usize::MAX {
-            result.push(circuits[*i].junction_box_count());
+        if let Some((x, y, z)) = parse_coords(line_num, line) {
+            junction_boxes.push(JunctionBox::new(x, y, z, idx));
+            idx += 1;
         }
     }
-    result
+    junction_boxes
 }
 
 // Binary crate entry point
@@ -527,54 +505,19 @@ fn main() -> Result<()> {
     let f = File::open(path).with_context(|| {
         format!("Could not open `{}`", path.display())
     })?;
-    let rdr = BufReader::new(f);
-    let lines = rdr.lines();
-
-    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*,\s*([0-9]+)\s*$")
-            .unwrap();
-    let mut line_num: usize = 0;
-    let mut idx: usize = 0;
-    for line in lines {
-        line_num += 1;
-        let line = line.unwrap();
-        let line = line.trim();
-        if 0 == line.len() {
-            continue;
-        }
-        if !re_coord.is_match(&line) {
-            println!(
-                "*** FAILED *** to match line {}: '{}'",
-                line_num, line
-            );
-            continue;
-        }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<i64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<i64>().unwrap();
-        let zs = coords.get(3).unwrap().as_str();
-        let z = zs.parse::<i64>().unwrap();
-        let junction_box: JunctionBox = JunctionBox::new(x, y, z, idx);
-        junction_boxes.push(junction_box);
-        idx += 1;
-    }
+    let mut junction_boxes: Vec<JunctionBox> =
+        file_to_junction_boxes(f);
 
     println!("found {} junction boxes", junction_boxes.len());
     println!("upto: {}", upto);
 
     // build the circuits and find the largest
     //
-    let circuit_sizes: Vec<usize> =
-        list_sizes_of_largest_circuits_fast(*upto, &mut junction_boxes);
-    println!("{:#?}", circuit_sizes);
-    let mut actual_product: usize = 1;
-    let end = *productoflargest;
-    for i in 0..end {
-        actual_product *= circuit_sizes[i];
-    }
+    let actual_product: usize = product_of_largest_circuits(
+        *upto,
+        *productoflargest,
+        &mut junction_boxes,
+    );
 
     // Display the grand total of problem answers
     //
@@ -640,38 +583,8 @@ fn list_sorted_pair_distances() {
 984,92,344
 425,690,689"
         .to_string();
-    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*,\s*([0-9]+)\s*$")
-            .unwrap();
-    let input = raw_input.as_str();
-    let lines = input.split('\n');
-    let mut line_num: usize = 0;
-    let mut idx: usize = 0;
-    for line in lines {
-        line_num += 1;
-        let line = line.trim();
-        if 0 == line.len() {
-            continue;
-        }
-        if !re_coord.is_match(&line) {
-            println!(
-                "*** FAILED *** to match line {}: '{}'",
-                line_num, line
-            );
-            continue;
-        }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<i64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<i64>().unwrap();
-        let zs = coords.get(3).unwrap().as_str();
-        let z = zs.parse::<i64>().unwrap();
-        let junction_box: JunctionBox = JunctionBox::new(x, y, z, idx);
-        junction_boxes.push(junction_box);
-        idx += 1;
-    }
+    let junction_boxes: Vec<JunctionBox> =
+        string_to_junction_boxes(raw_input.as_str());
 
     // find all the distances
     //
@@ -730,38 +643,8 @@ fn given_example_part1() {
 984,92,344
 425,690,689"
         .to_string();
-    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*,\s*([0-9]+)\s*$")
-            .unwrap();
-    let input = raw_input.as_str();
-    let lines = input.split('\n');
-    let mut line_num: usize = 0;
-    let mut idx: usize = 0;
-    for line in lines {
-        line_num += 1;
-        let line = line.trim();
-        if 0 == line.len() {
-            continue;
-        }
-        if !re_coord.is_match(&line) {
-            println!(
-                "*** FAILED *** to match line {}: '{}'",
-                line_num, line
-            );
-            continue;
-        }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<i64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<i64>().unwrap();
-        let zs = coords.get(3).unwrap().as_str();
-        let z = zs.parse::<i64>().unwrap();
-        let junction_box: JunctionBox = JunctionBox::new(x, y, z, idx);
-        junction_boxes.push(junction_box);
-        idx += 1;
-    }
+    let mut junction_boxes: Vec<JunctionBox> =
+        string_to_junction_boxes(raw_input.as_str());
 
     // build the circuits and find the largest
     //
@@ -773,3 +656,40 @@ fn given_example_part1() {
     }
     assert_eq!(expected_product, actual_product);
 }
+
+// test the solve used by main with the example input and the
+// CLI values from the puzzle specs
+//
+#[test]
+fn given_example_product_of_largest() {
+    let upto: usize = 10;
+    let productoflargest: usize = 3;
+    let mut junction_boxes: Vec<JunctionBox> =
+        string_to_junction_boxes(DAY08_EXAMPLE);
+    let actual_product = product_of_largest_circuits(
+        upto,
+        productoflargest,
+        &mut junction_boxes,
+    );
+    assert_eq!(40, actual_product);
+
+    // the circuits are the ones day08a's tests expect from the
+    // same shared example
+    //
+    let mut distance_by_pair: BTreeMap<String, u64> = BTreeMap::new();
+    list_all_pair_distances(&junction_boxes, &mut distance_by_pair);
+    let sorted_pairs = sort_pairs_by_distance(&distance_by_pair);
+    assert_eq!(
+        DAY08_EXAMPLE_SIZES_AFTER_10.to_vec(),
+        assemble_circuits(upto, &sorted_pairs, junction_boxes.len())
+    );
+
+    // as are the largest circuits from the original search
+    //
+    let circuit_sizes: Vec<usize> =
+        list_sizes_of_largest_circuits(upto, &mut junction_boxes);
+    assert_eq!(
+        DAY08_EXAMPLE_SIZES_AFTER_10[0..productoflargest],
+        circuit_sizes[0..productoflargest]
+    );
+}
@@ -1,13 +1,16 @@
 use ::std::cmp::Ordering;
 use ::std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::ops::Range;
 use std::path::PathBuf;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+#[cfg(test)]
+use aoc_common::{DAY08_EXAMPLE, DAY08_EXAMPLE_SIZES_AFTER_10};
+use aoc_common::{UnionFind, parse_labeled_point3};
 use clap::{Id, Parser};
-use regex::Regex;
 
 /// Given input file containing the problem set,
 /// repeatedly connect the next closest junction boxes,
@@ -30,10 +33,80 @@ struct Cli {
     /// to produce the product of their sizes, default 3
     #[arg(short = 'p', long = "product-terms")]
     productoflargest: Option<usize>,
+    /// connect the closest junction boxes until only this
+    /// many clusters remain, then report the cluster sizes
+    #[arg(short = 'k', long = "clusters")]
+    clusters: Option<usize>,
+    /// print each connection made, with its length
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+    /// add the junction boxes one at a time and connect them
+    /// incrementally, rather than sorting every pair up front;
+    /// only reports the product of the largest circuits
+    #[arg(
+        short = 'i',
+        long = "incremental",
+        conflicts_with_all = [
+            "connectall",
+            "clusters",
+            "verbose",
+            "progress",
+            "connections_out",
+            "target_size",
+        ]
+    )]
+    incremental: bool,
+    /// show a progress bar while building the circuits
+    #[arg(long = "progress")]
+    progress: bool,
+    /// write the connections made, in order, to this file, one
+    /// "a,b" pair of box ids per line
+    #[arg(long = "connections-out")]
+    connections_out: Option<PathBuf>,
+    /// ignore the z coordinates, measuring distances in the
+    /// xy-plane
+    #[arg(long = "ignore-z")]
+    ignore_z: bool,
+    /// report how many connections, in distance order, are made
+    /// before some circuit first holds this many junction boxes
+    #[arg(long = "target-size")]
+    target_size: Option<usize>,
     /// The path to the file containing battery bank specs
     path: PathBuf,
 }
 
+#[derive(Debug, PartialEq)]
+enum CircuitError {
+    // the number of circuits asked for, and the number there are
+    TooFewCircuits(usize, usize),
+    // the number of junction boxes, and the number of circuits
+    // they remain in, counting unconnected boxes as circuits
+    NotAllConnected(usize, usize),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitError::TooFewCircuits(requested, available) => {
+                write!(
+                    f,
+                    "Cannot multiply the sizes of the largest {} circuits; there are only {}",
+                    requested, available
+                )
+            }
+            CircuitError::NotAllConnected(box_count, remaining) => {
+                write!(
+                    f,
+                    "Could not connect all {} junction boxes into a single circuit ({} circuits remain)",
+                    box_count, remaining
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
 #[derive(Debug)]
 struct Point {
     x: i64,
@@ -52,32 +125,93 @@ impl Point {
         let dz: u64 = (self.z - other.z).abs().try_into().unwrap();
         dx * dx + dy * dy + dz * dz
     }
+
+    // the straight line (not squared) distance between points
+    //
+    fn euclidean_distance(&self, other: &Point) -> f64 {
+        (self.distance_from(other) as f64).sqrt()
+    }
+}
+
+// How the distance between junction boxes is measured
+//
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Metric {
+    // by all three coordinates
+    #[default]
+    Xyz,
+    // by the x and y coordinates only, as though every box were
+    // projected onto the xy-plane
+    Xy,
+}
+
+impl Metric {
+    // the point whose distances this metric measures, e.g. with
+    // a z of 0 in the xy-plane; the point itself is unchanged
+    //
+    fn project(&self, p: &Point) -> Point {
+        match self {
+            Metric::Xyz => Point::new(p.x, p.y, p.z),
+            Metric::Xy => Point::new(p.x, p.y, 0),
+        }
+    }
 }
 
 #[derive(Debug)]
 struct JunctionBox {
     location: Point,
     id: usize,
+    // the label given in the input, if any, e.g. "A" for "A: 1,2,3"
+    label: Option<String>,
 }
 
 impl JunctionBox {
+    #[allow(dead_code)]
     fn new(x: i64, y: i64, z: i64, id: usize) -> Self {
+        Self::new_labeled(x, y, z, id, None)
+    }
+
+    // constructor for a box that may carry a label from the input
+    //
+    fn new_labeled(
+        x: i64,
+        y: i64,
+        z: i64,
+        id: usize,
+        label: Option<String>,
+    ) -> Self {
         let p: Point = Point::new(x, y, z);
         JunctionBox {
             location: p,
             id: id,
+            label,
         }
     }
 
-    fn distance_from(&self, other: &Self) -> u64 {
-        self.location.distance_from(&other.location)
+    fn distance_from(&self, other: &Self, metric: Metric) -> u64 {
+        metric
+            .project(&self.location)
+            .distance_from(&metric.project(&other.location))
+    }
+
+    fn euclidean_distance(&self, other: &Self, metric: Metric) -> f64 {
+        metric
+            .project(&self.location)
+            .euclidean_distance(&metric.project(&other.location))
     }
 
+    // the coordinates, e.g. "(1,2,3)", preceded by the label if
+    // the box has one, e.g. "A (1,2,3)"
+    //
     fn describe_coords(&self) -> String {
-        format!(
+        let coords = format!(
             "({},{},{})",
             self.location.x, self.location.y, self.location.z
-        )
+        );
+        match &self.label {
+            Some(label) => format!("{} {}", label, coords),
+            None => coords,
+        }
     }
 }
 
@@ -157,6 +291,224 @@ impl Circuit {
     }
 }
 
+// A node of the k-d tree, holding one junction box
+//
+struct KdNode {
+    id: usize,
+    coords: [i64; 3],
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// A 3-dimensional tree of junction box locations that supports
+// inserting boxes one at a time and finding the nearest box
+// that satisfies a condition. Nodes split on x, y and z in turn.
+//
+struct KdTree {
+    nodes: Vec<KdNode>,
+}
+
+impl KdTree {
+    fn new() -> Self {
+        KdTree { nodes: Vec::new() }
+    }
+
+    fn insert(&mut self, id: usize, location: &Point) {
+        let coords = [location.x, location.y, location.z];
+        let new_idx = self.nodes.len();
+        self.nodes.push(KdNode {
+            id,
+            coords,
+            left: None,
+            right: None,
+        });
+        if 0 == new_idx {
+            return;
+        }
+        let mut idx: usize = 0;
+        let mut axis: usize = 0;
+        loop {
+            let node = &mut self.nodes[idx];
+            let child = if coords[axis] < node.coords[axis] {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+            match *child {
+                Some(next) => {
+                    idx = next;
+                    axis = (axis + 1) % 3;
+                }
+                None => {
+                    *child = Some(new_idx);
+                    return;
+                }
+            }
+        }
+    }
+
+    // Find the box nearest the location for which accept returns
+    // true. Boxes at equal distances are ordered by id.
+    //
+    // Returns the (squared distance, id) of the box, or None if
+    // no box is accepted.
+    //
+    fn nearest<F: Fn(usize) -> bool>(
+        &self,
+        location: &Point,
+        accept: &F,
+    ) -> Option<(u64, usize)> {
+        let coords = [location.x, location.y, location.z];
+        let mut best: Option<(u64, usize)> = None;
+        if !self.nodes.is_empty() {
+            self.nearest_from(0, 0, &coords, accept, &mut best);
+        }
+        best
+    }
+
+    fn nearest_from<F: Fn(usize) -> bool>(
+        &self,
+        idx: usize,
+        axis: usize,
+        coords: &[i64; 3],
+        accept: &F,
+        best: &mut Option<(u64, usize)>,
+    ) {
+        let node = &self.nodes[idx];
+        if accept(node.id) {
+            let mut dist: u64 = 0;
+            for (a, b) in node.coords.iter().zip(coords.iter()) {
+                let d = (a - b).unsigned_abs();
+                dist += d * d;
+            }
+            let candidate = (dist, node.id);
+            if best.is_none_or(|b| candidate < b) {
+                *best = Some(candidate);
+            }
+        }
+        let diff = coords[axis] - node.coords[axis];
+        let (near, far) = if diff < 0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        let next_axis = (axis + 1) % 3;
+        if let Some(near) = near {
+            self.nearest_from(near, next_axis, coords, accept, best);
+        }
+        // only search the far side if it could hold a box at least
+        // as close as the best so far
+        //
+        let plane_dist = diff.unsigned_abs() * diff.unsigned_abs();
+        if let Some(far) = far
+            && best.is_none_or(|b| plane_dist <= b.0)
+        {
+            self.nearest_from(far, next_axis, coords, accept, best);
+        }
+    }
+}
+
+// Builds circuits incrementally: junction boxes can be added at
+// any time, and each connection step joins the closest pair of
+// boxes that has not been connected yet.
+//
+struct CircuitBuilder {
+    junction_boxes: Vec<JunctionBox>,
+    tree: KdTree,
+    circuits: UnionFind,
+    // the pairs already connected, as (lower id, higher id)
+    paired: BTreeSet<(usize, usize)>,
+    // how the distance between boxes is measured
+    metric: Metric,
+}
+
+impl CircuitBuilder {
+    fn new(metric: Metric) -> Self {
+        CircuitBuilder {
+            junction_boxes: Vec::new(),
+            tree: KdTree::new(),
+            circuits: UnionFind::new(0),
+            paired: BTreeSet::new(),
+            metric,
+        }
+    }
+
+    // add a junction box; it starts in a circuit of its own
+    //
+    fn add_box(&mut self, junction_box: JunctionBox) {
+        let id = self.circuits.add();
+        self.tree
+            .insert(id, &self.metric.project(&junction_box.location));
+        self.junction_boxes.push(junction_box);
+    }
+
+    // Connect the closest pair of boxes not yet connected, in the
+    // same order as sort_pairs_by_distance.
+    //
+    // Returns the pair connected, or None if every pair already is.
+    //
+    fn connect_step(&mut self) -> Option<(usize, usize)> {
+        let mut best: Option<(u64, usize, usize)> = None;
+        for (id_a, jb) in self.junction_boxes.iter().enumerate() {
+            let paired = &self.paired;
+            let accept = |id_b: usize| {
+                (id_b != id_a)
+                    && !paired
+                        .contains(&(id_a.min(id_b), id_a.max(id_b)))
+            };
+            if let Some((dist, id_b)) = self
+                .tree
+                .nearest(&self.metric.project(&jb.location), &accept)
+            {
+                let candidate = (dist, id_a.min(id_b), id_a.max(id_b));
+                if best.is_none_or(|b| candidate < b) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        let (_, id_a, id_b) = best?;
+        self.paired.insert((id_a, id_b));
+        self.circuits.union(id_a, id_b);
+        Some((id_a, id_b))
+    }
+
+    // the number of boxes in each circuit, in descending order
+    //
+    fn circuit_sizes(&mut self) -> Vec<usize> {
+        self.circuits.component_sizes()
+    }
+}
+
+// Join the closest pairs of junction boxes (building the minimum
+// spanning tree) until only the requested number of clusters
+// remain.
+//
+// Returns the sizes of the clusters in descending order.
+//
+fn build_clusters(
+    cluster_count: usize,
+    sorted_pairs: &[(usize, usize)],
+    jb_count: usize,
+) -> Vec<usize> {
+    let mut uf = UnionFind::new(jb_count);
+    for (id_a, id_b) in sorted_pairs.iter() {
+        if uf.component_count() <= cluster_count {
+            break;
+        }
+        uf.union(*id_a, *id_b);
+    }
+    uf.component_sizes()
+}
+
+// The most junction boxes for which find_distances recurses; it
+// recurses once per box, so above this it iterates instead to
+// avoid overflowing the stack.
+//
+const FIND_DISTANCES_RECURSION_THRESHOLD: usize = 1000;
+
+// Find the distance between every pair of junction boxes in the
+// range.
+//
 fn find_distances(
     junction_boxes: &Vec<JunctionBox>,
     pairs_by_first_id: &mut BTreeMap<
@@ -164,6 +516,84 @@ fn find_distances(
         BTreeMap<usize, JunctionBoxPair>,
     >,
     rng: Range<usize>,
+    metric: Metric,
+) {
+    find_distances_with_threshold(
+        junction_boxes,
+        pairs_by_first_id,
+        rng,
+        metric,
+        FIND_DISTANCES_RECURSION_THRESHOLD,
+    );
+}
+
+// Find the distance between every pair of junction boxes in the
+// range, recursing if the range holds no more than threshold
+// boxes and iterating otherwise.
+//
+fn find_distances_with_threshold(
+    junction_boxes: &Vec<JunctionBox>,
+    pairs_by_first_id: &mut BTreeMap<
+        usize,
+        BTreeMap<usize, JunctionBoxPair>,
+    >,
+    rng: Range<usize>,
+    metric: Metric,
+    threshold: usize,
+) {
+    if rng.len() <= threshold {
+        find_distances_recursive(
+            junction_boxes,
+            pairs_by_first_id,
+            rng,
+            metric,
+        );
+    } else {
+        find_distances_iterative(
+            junction_boxes,
+            pairs_by_first_id,
+            rng,
+            metric,
+        );
+    }
+}
+
+// Find the pair distances without recursing, so any number of
+// junction boxes can be handled.
+//
+fn find_distances_iterative(
+    junction_boxes: &[JunctionBox],
+    pairs_by_first_id: &mut BTreeMap<
+        usize,
+        BTreeMap<usize, JunctionBoxPair>,
+    >,
+    rng: Range<usize>,
+    metric: Metric,
+) {
+    let end: usize = rng.end;
+    for id_a in rng {
+        let paired_with = pairs_by_first_id.entry(id_a).or_default();
+        for id_b in (id_a + 1)..end {
+            paired_with.entry(id_b).or_insert_with(|| {
+                let dist: u64 = junction_boxes[id_a]
+                    .distance_from(&junction_boxes[id_b], metric);
+                JunctionBoxPair::new(id_a, id_b, dist)
+            });
+        }
+        if paired_with.is_empty() {
+            pairs_by_first_id.remove(&id_a);
+        }
+    }
+}
+
+fn find_distances_recursive(
+    junction_boxes: &Vec<JunctionBox>,
+    pairs_by_first_id: &mut BTreeMap<
+        usize,
+        BTreeMap<usize, JunctionBoxPair>,
+    >,
+    rng: Range<usize>,
+    metric: Metric,
 ) {
     let id_a: usize = rng.start;
     let end: usize = rng.end;
@@ -171,7 +601,12 @@ fn find_distances(
         return;
     }
     let start = id_a + 1;
-    find_distances(junction_boxes, pairs_by_first_id, start..end);
+    find_distances_recursive(
+        junction_boxes,
+        pairs_by_first_id,
+        start..end,
+        metric,
+    );
     for id_b in start..end {
         if pairs_by_first_id.contains_key(&id_a) {
             let paired_with = pairs_by_first_id.get(&id_a).unwrap();
@@ -179,8 +614,8 @@ fn find_distances(
                 continue;
             }
         }
-        let dist: u64 =
-            junction_boxes[id_a].distance_from(&junction_boxes[id_b]);
+        let dist: u64 = junction_boxes[id_a]
+            .distance_from(&junction_boxes[id_b], metric);
         let pair = JunctionBoxPair::new(id_a, id_b, dist);
         if !pairs_by_first_id.contains_key(&id_a) {
             let mut paired_with: BTreeMap<usize, JunctionBoxPair> =
@@ -206,13 +641,20 @@ fn sort_pairs_by_distance(
             local_list.push((*key_a, *key_b, jb.distance));
         }
     }
+    // sort by distance; pairs at equal distance are ordered by
+    // their first box id and then by their second box id, so the
+    // circuits are always built in the same order. Box ids number
+    // the boxes in input order, so among equal-distance pairs the
+    // one whose earlier box appeared first in the input wins, and
+    // then the one whose later box did.
+    //
     local_list.sort_by(|a, b| {
         if a.2 > b.2 {
             Ordering::Greater
         } else if a.2 < b.2 {
             Ordering::Less
         } else {
-            Ordering::Equal
+            (a.0, a.1).cmp(&(b.0, b.1))
         }
     });
     for (id_a, id_b, _) in local_list.iter() {
@@ -220,16 +662,81 @@ fn sort_pairs_by_distance(
     }
 }
 
+// Describe a connection between two junction boxes, with both
+// the squared and the Euclidean distance between them by the
+// metric.
+//
+fn describe_connection(
+    junction_boxes: &[JunctionBox],
+    id_a: usize,
+    id_b: usize,
+    metric: Metric,
+) -> String {
+    let a = &junction_boxes[id_a];
+    let b = &junction_boxes[id_b];
+    format!(
+        "{}-{} {} to {}: squared distance {}, length {:.3}",
+        id_a,
+        id_b,
+        a.describe_coords(),
+        b.describe_coords(),
+        a.distance_from(b, metric),
+        a.euclidean_distance(b, metric)
+    )
+}
+
+// Render a progress bar of the given width for current out of
+// total steps, e.g. "[#####     ] 5/10"
+//
+fn progress_bar(current: usize, total: usize, width: usize) -> String {
+    let filled = match (current * width).checked_div(total) {
+        Some(filled) => usize::min(width, filled),
+        None => width,
+    };
+    format!(
+        "[{}{}] {}/{}",
+        "#".repeat(filled),
+        " ".repeat(width - filled),
+        current,
+        total
+    )
+}
+
+// Connect the sorted pairs in order, at most upto of them,
+// stopping early once every box is in one circuit. Ties between
+// pairs at equal distance are settled by sort_pairs_by_distance,
+// by input order, so the circuits built do not depend on how the
+// sort treats equal elements.
+//
+// Every pair consumed is appended to connections, in order, so
+// passing connections back in as the sorted pairs replays the
+// build.
+//
+// If given, progress is called with (current, total) pairs
+// processed about every hundredth of the way, and with
+// (total, total) when the build is done.
+//
 fn build_circuits(
     upto: &usize,
     sorted_pairs: &Vec<(usize, usize)>,
     last_two: &mut (usize, usize),
+    connections: &mut Vec<(usize, usize)>,
     jb_count: usize,
+    progress: Option<&dyn Fn(usize, usize)>,
 ) -> BTreeMap<usize, Circuit> {
     let mut next_id: usize = 0;
     let mut circuits: BTreeMap<usize, Circuit> = BTreeMap::new();
     let upto = usize::min(*upto, sorted_pairs.len());
+    let report_every = usize::max(1, upto / 100);
+    let mut reported: usize = 0;
     for pass in 0..upto {
+        if let Some(progress) = progress
+            && (0 < pass)
+            && (0 == pass % report_every)
+        {
+            progress(pass, upto);
+            reported = pass;
+        }
         let (id_a, id_b) = sorted_pairs[pass];
         // println!("({}-{})", id_a, id_b);
         let circuit_ids: Vec<usize> =
@@ -244,6 +751,7 @@ fn build_circuits(
                 break;
             }
         }
+        connections.push((id_a, id_b));
         let mut target_circuit_ids: Vec<usize> = Vec::new();
         for id in circuit_ids {
             let circuit = circuits.get_mut(&id).unwrap();
@@ -295,9 +803,187 @@ fn build_circuits(
         // }
         // println!("{}", bld.join(" "));
     }
+    if let Some(progress) = progress
+        && (reported < upto)
+    {
+        progress(upto, upto);
+    }
     circuits
 }
 
+// The boxes each box was directly connected to, keyed by box id,
+// from the pairs build_circuits consumed. Unlike circuit
+// membership, this keeps the actual connections, including those
+// made between boxes already in the same circuit. Boxes never
+// connected are left out.
+//
+#[allow(dead_code)]
+fn adjacency(
+    consumed_pairs: &[(usize, usize)],
+) -> BTreeMap<usize, BTreeSet<usize>> {
+    let mut neighbors: BTreeMap<usize, BTreeSet<usize>> =
+        BTreeMap::new();
+    for (id_a, id_b) in consumed_pairs.iter() {
+        neighbors.entry(*id_a).or_default().insert(*id_b);
+        neighbors.entry(*id_b).or_default().insert(*id_a);
+    }
+    neighbors
+}
+
+// Write the connections, one "a,b" pair of box ids per line, in
+// the order they were made.
+//
+fn write_connections<W: Write>(
+    out: &mut W,
+    connections: &[(usize, usize)],
+) -> std::io::Result<()> {
+    for (id_a, id_b) in connections.iter() {
+        writeln!(out, "{},{}", id_a, id_b)?;
+    }
+    Ok(())
+}
+
+// The fewest connections, in distance order, that build_circuits
+// makes before some circuit holds at least target_size junction
+// boxes. Every box starts as a circuit of one, so a target of one
+// takes no connections. The largest circuit never shrinks as
+// connections are made, so this binary searches over the number
+// of connections.
+//
+// Returns None if no circuit reaches target_size.
+//
+fn connections_to_reach_size(
+    target_size: usize,
+    sorted_pairs: &Vec<(usize, usize)>,
+    jb_count: usize,
+) -> Option<usize> {
+    let largest_after = |upto: usize| -> usize {
+        let mut last_two: (usize, usize) = (0, 0);
+        let mut connections: Vec<(usize, usize)> = Vec::new();
+        let circuits = build_circuits(
+            &upto,
+            sorted_pairs,
+            &mut last_two,
+            &mut connections,
+            jb_count,
+            None,
+        );
+        circuits.values().map(|c| c.len()).max().unwrap_or(1)
+    };
+    if (0 == jb_count) || (jb_count < target_size) {
+        return None;
+    }
+    if 1 >= target_size {
+        return Some(0);
+    }
+    let mut lo: usize = 1;
+    let mut hi: usize = sorted_pairs.len();
+    if largest_after(hi) < target_size {
+        return None;
+    }
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if largest_after(mid) < target_size {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(lo)
+}
+
+// Returns true if the circuits form a single component holding
+// every junction box exactly once; otherwise false.
+//
+fn verify_single_component(
+    circuits: &BTreeMap<usize, Circuit>,
+    box_count: usize,
+) -> bool {
+    if 1 != circuits.len() {
+        return false;
+    }
+    let circuit = circuits.values().next().unwrap();
+    (box_count == circuit.len())
+        && (0..box_count).all(|id| circuit.contains(id))
+}
+
+// Find the two boxes in the circuit that are farthest apart, i.e.
+// the circuit's diameter. Checks every pair of boxes in the
+// circuit, so takes O(m^2) time for a circuit of m boxes.
+//
+// Returns None if the circuit has fewer than two boxes; otherwise
+// Some((id_a, id_b, squared distance)) with id_a < id_b. Of pairs
+// at equal distance, the one with the lowest ids is returned.
+//
+#[allow(dead_code)]
+fn circuit_diameter(
+    circuit: &Circuit,
+    boxes: &[JunctionBox],
+    metric: Metric,
+) -> Option<(usize, usize, u64)> {
+    let ids: Vec<usize> = circuit.jbs.iter().copied().collect();
+    let mut farthest: Option<(usize, usize, u64)> = None;
+    for (i, id_a) in ids.iter().enumerate() {
+        for id_b in ids[i + 1..].iter() {
+            let dist =
+                boxes[*id_a].distance_from(&boxes[*id_b], metric);
+            match farthest {
+                Some((_, _, max_dist)) if dist <= max_dist => {}
+                _ => farthest = Some((*id_a, *id_b, dist)),
+            }
+        }
+    }
+    farthest
+}
+
+// The least total squared wire length that connects every box in
+// the circuit, i.e. the weight of a minimum spanning tree of just
+// the circuit's boxes, found with Prim's algorithm. Takes O(m^2)
+// time for a circuit of m boxes.
+//
+// Returns 0 if the circuit has fewer than two boxes.
+//
+#[allow(dead_code)]
+fn circuit_mst_length(
+    circuit: &Circuit,
+    boxes: &[JunctionBox],
+    metric: Metric,
+) -> u64 {
+    let ids: Vec<usize> = circuit.jbs.iter().copied().collect();
+    if ids.len() < 2 {
+        return 0;
+    }
+    // the squared distance from each box to the tree, or None
+    // once the box is in the tree
+    //
+    let mut to_tree: Vec<Option<u64>> = ids
+        .iter()
+        .map(|id| {
+            Some(boxes[ids[0]].distance_from(&boxes[*id], metric))
+        })
+        .collect();
+    to_tree[0] = None;
+    let mut length: u64 = 0;
+    for _ in 1..ids.len() {
+        let (next, dist) = to_tree
+            .iter()
+            .enumerate()
+            .filter_map(|(i, d)| d.map(|d| (i, d)))
+            .min_by_key(|(_, d)| *d)
+            .unwrap();
+        length += dist;
+        to_tree[next] = None;
+        for (i, d) in to_tree.iter_mut().enumerate() {
+            if let Some(d) = d {
+                let via_next = boxes[ids[next]]
+                    .distance_from(&boxes[ids[i]], metric);
+                *d = u64::min(*d, via_next);
+            }
+        }
+    }
+    length
+}
+
 fn sort_circuits(
     circuits: &BTreeMap<usize, Circuit>,
 ) -> Vec<(usize, usize)> {
@@ -322,6 +1008,66 @@ fn sort_circuits(
     sorted_circuits
 }
 
+// The sizes of every circuit in descending order, counting each
+// junction box not yet connected to another as a circuit of one
+//
+fn circuit_sizes_with_singles(
+    sorted_circuits: &[(usize, usize)],
+    box_count: usize,
+) -> Vec<usize> {
+    let mut sizes: Vec<usize> =
+        sorted_circuits.iter().map(|(_, len)| *len).collect();
+    let connected: usize = sizes.iter().sum();
+    sizes.extend(std::iter::repeat_n(1, box_count - connected));
+    sizes
+}
+
+// Multiply the sizes of the largest count circuits, given the
+// sizes of every circuit in descending order.
+//
+// Returns an error if there are fewer than count circuits.
+//
+fn product_of_largest(
+    sizes: &[usize],
+    count: usize,
+) -> Result<u64, CircuitError> {
+    if count > sizes.len() {
+        return Err(CircuitError::TooFewCircuits(count, sizes.len()));
+    }
+    let mut product: u64 = 1;
+    for size in sizes[..count].iter() {
+        product *= u64::try_from(*size).unwrap();
+    }
+    Ok(product)
+}
+
+// Multiply the x coordinates of the last two boxes connected,
+// which joined every box into a single circuit.
+//
+// Returns an error if the pairs ran out before every box was in
+// one circuit, since then the last two boxes connected did not
+// complete anything.
+//
+fn last_connection_product(
+    junction_boxes: &[JunctionBox],
+    circuits: &BTreeMap<usize, Circuit>,
+    last_two: (usize, usize),
+) -> Result<u64, CircuitError> {
+    let box_count = junction_boxes.len();
+    if !verify_single_component(circuits, box_count) {
+        let connected: usize = circuits.values().map(|c| c.len()).sum();
+        return Err(CircuitError::NotAllConnected(
+            box_count,
+            circuits.len() + box_count - connected,
+        ));
+    }
+    Ok(u64::try_from(
+        junction_boxes[last_two.0].location.x
+            * junction_boxes[last_two.1].location.x,
+    )
+    .unwrap())
+}
+
 // Binary crate entry point
 //
 fn main() -> Result<()> {
@@ -335,6 +1081,11 @@ fn main() -> Result<()> {
         productoflargest = x;
     }
     let connect_all = args.connectall;
+    let metric = if args.ignore_z {
+        Metric::Xy
+    } else {
+        Metric::Xyz
+    };
     let path = &args.path;
 
     let f = File::open(path).with_context(|| {
@@ -344,9 +1095,6 @@ fn main() -> Result<()> {
     let lines = rdr.lines();
 
     let mut junction_boxes: Vec<JunctionBox> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*,\s*([0-9]+)\s*$")
-            .unwrap();
     let mut line_num: usize = 0;
     let mut idx: usize = 0;
     for line in lines {
@@ -356,27 +1104,44 @@ fn main() -> Result<()> {
         if 0 == line.len() {
             continue;
         }
-        if !re_coord.is_match(&line) {
+        let Some((label, (x, y, z))) = parse_labeled_point3(line)
+        else {
             println!(
                 "*** FAILED *** to match line {}: '{}'",
                 line_num, line
             );
             continue;
-        }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<i64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<i64>().unwrap();
-        let zs = coords.get(3).unwrap().as_str();
-        let z = zs.parse::<i64>().unwrap();
-        let junction_box: JunctionBox = JunctionBox::new(x, y, z, idx);
+        };
+        // ids follow input order; sort_pairs_by_distance breaks
+        // ties between equal-distance pairs by them
+        //
+        let junction_box: JunctionBox =
+            JunctionBox::new_labeled(x, y, z, idx, label);
         junction_boxes.push(junction_box);
         idx += 1;
     }
 
     println!("found {} junction boxes", junction_boxes.len());
 
+    if args.incremental {
+        let mut builder = CircuitBuilder::new(metric);
+        for junction_box in junction_boxes {
+            builder.add_box(junction_box);
+        }
+        for _ in 0..upto {
+            if builder.connect_step().is_none() {
+                break;
+            }
+        }
+        let sizes = builder.circuit_sizes();
+        let product = product_of_largest(&sizes, productoflargest)?;
+        println!(
+            "Product of the largest {} circuits is {}",
+            productoflargest, product
+        );
+        return Ok(());
+    }
+
     // for jb in junction_boxes.iter() {
     //     println!("{}: {}", jb.id, jb.describe_coords());
     // }
@@ -384,7 +1149,7 @@ fn main() -> Result<()> {
     let len = junction_boxes.len();
     let mut pairs: BTreeMap<usize, BTreeMap<usize, JunctionBoxPair>> =
         BTreeMap::new();
-    find_distances(&junction_boxes, &mut pairs, 0..len);
+    find_distances(&junction_boxes, &mut pairs, 0..len, metric);
 
     // for key_a in pairs.keys() {
     //     let paired_with = pairs.get(key_a).unwrap();
@@ -400,6 +1165,41 @@ fn main() -> Result<()> {
     let mut sorted_pairs: Vec<(usize, usize)> = Vec::new();
     sort_pairs_by_distance(&pairs, &mut sorted_pairs);
 
+    if let Some(cluster_count) = args.clusters {
+        let sizes = build_clusters(
+            cluster_count,
+            &sorted_pairs,
+            junction_boxes.len(),
+        );
+        let l: Vec<String> =
+            sizes.iter().map(|x| x.to_string()).collect();
+        println!(
+            "Sizes of the {} clusters: {}",
+            sizes.len(),
+            l.join(",")
+        );
+        return Ok(());
+    }
+
+    if let Some(target_size) = args.target_size {
+        let Some(count) = connections_to_reach_size(
+            target_size,
+            &sorted_pairs,
+            junction_boxes.len(),
+        ) else {
+            bail!(
+                "No circuit reaches {} junction boxes; there are only {} boxes",
+                target_size,
+                junction_boxes.len()
+            );
+        };
+        println!(
+            "A circuit first holds {} junction boxes after {} connections",
+            target_size, count
+        );
+        return Ok(());
+    }
+
     if connect_all {
         upto = usize::MAX;
     }
@@ -419,13 +1219,51 @@ fn main() -> Result<()> {
     //     count += 1;
     // }
 
+    let show_progress = |current: usize, total: usize| {
+        eprint!(
+            "\rbuilding circuits {}",
+            progress_bar(current, total, 50)
+        );
+    };
+    let progress: Option<&dyn Fn(usize, usize)> = if args.progress {
+        Some(&show_progress)
+    } else {
+        None
+    };
     let mut last_two: (usize, usize) = (0, 0);
+    let mut connections: Vec<(usize, usize)> = Vec::new();
     let circuits = build_circuits(
         &upto,
         &sorted_pairs,
         &mut last_two,
+        &mut connections,
         junction_boxes.len(),
+        progress,
     );
+    if args.progress {
+        eprintln!();
+    }
+    if let Some(out_path) = &args.connections_out {
+        let mut out = File::create(out_path).with_context(|| {
+            format!("Could not create `{}`", out_path.display())
+        })?;
+        write_connections(&mut out, &connections).with_context(
+            || format!("Could not write `{}`", out_path.display()),
+        )?;
+    }
+    if args.verbose {
+        for (id_a, id_b) in connections.iter() {
+            println!(
+                "{}",
+                describe_connection(
+                    &junction_boxes,
+                    *id_a,
+                    *id_b,
+                    metric
+                )
+            );
+        }
+    }
 
     // println!("CIRCUITS:");
     // for circuit_id in circuits.keys() {
@@ -437,23 +1275,22 @@ fn main() -> Result<()> {
     // }
 
     if connect_all {
-        let product: u64 = u64::try_from(
-            junction_boxes[last_two.0].location.x
-                * junction_boxes[last_two.1].location.x,
-        )
-        .unwrap();
+        let product = last_connection_product(
+            &junction_boxes,
+            &circuits,
+            last_two,
+        )?;
         println!(
             "Product of the x coord of last two boxes connected is {}",
             product
         );
     } else {
         let sorted_circuits = sort_circuits(&circuits);
-        let mut product: u64 = 1;
-        let limit: usize = productoflargest;
-        for i in 0..limit {
-            let len: u64 = u64::try_from(sorted_circuits[i].1).unwrap();
-            product *= len;
-        }
+        let sizes = circuit_sizes_with_singles(
+            &sorted_circuits,
+            junction_boxes.len(),
+        );
+        let product = product_of_largest(&sizes, productoflargest)?;
         println!(
             "Product of the largest {} circuits is {}",
             productoflargest, product
@@ -467,15 +1304,34 @@ fn main() -> Result<()> {
 fn check_distance_1() {
     let a = JunctionBox::new(162, 187, 812, 0);
     let b = JunctionBox::new(425, 690, 689, 1);
-    let dist = a.distance_from(&b);
+    let dist = a.distance_from(&b, Metric::Xyz);
     assert_eq!(337307, dist);
 }
 
+#[test]
+fn check_connection_length() {
+    let junction_boxes = vec![
+        JunctionBox::new(162, 187, 812, 0),
+        JunctionBox::new(425, 690, 689, 1),
+    ];
+    let description =
+        describe_connection(&junction_boxes, 0, 1, Metric::Xyz);
+    assert!(description.contains("squared distance 337307"));
+    let length: f64 = description
+        .rsplit("length ")
+        .next()
+        .unwrap()
+        .parse::<f64>()
+        .unwrap();
+    let expected: f64 = (337307_f64).sqrt();
+    assert!((expected - length).abs() < 0.001);
+}
+
 #[test]
 fn check_distance_2() {
     let a = JunctionBox::new(739, 650, 466, 0);
     let b = JunctionBox::new(346, 949, 466, 1);
-    let dist = a.distance_from(&b);
+    let dist = a.distance_from(&b, Metric::Xyz);
     assert_eq!(243850, dist);
 }
 
@@ -511,9 +1367,6 @@ fn given_example_part1() {
 425,690,689"
         .to_string();
     let mut junction_boxes: Vec<JunctionBox> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*,\s*([0-9]+)\s*$")
-            .unwrap();
     let input = raw_input.as_str();
     let lines = input.split('\n');
     let mut line_num: usize = 0;
@@ -524,22 +1377,647 @@ fn given_example_part1() {
         if 0 == line.len() {
             continue;
         }
-        if !re_coord.is_match(&line) {
+        let Some((_, (x, y, z))) = parse_labeled_point3(line) else {
             println!(
                 "*** FAILED *** to match line {}: '{}'",
                 line_num, line
             );
             continue;
-        }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<i64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<i64>().unwrap();
-        let zs = coords.get(3).unwrap().as_str();
-        let z = zs.parse::<i64>().unwrap();
+        };
         let junction_box: JunctionBox = JunctionBox::new(x, y, z, idx);
         junction_boxes.push(junction_box);
         idx += 1;
     }
+
+    // day08 gets the same product from the same input (see its
+    // given_example_product_of_largest test)
+    //
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+    let (circuits, _, _) =
+        testhelper_build_circuits(upto, &sorted_pairs, len);
+    let sorted_circuits = sort_circuits(&circuits);
+    let mut actual_product: usize = 1;
+    for i in 0..productoflargest {
+        actual_product *= sorted_circuits[i].1;
+    }
+    assert_eq!(expected_product, actual_product);
+}
+
+// Find the distances between the boxes by the metric and sort the
+// pairs by them
+//
+#[cfg(test)]
+fn testhelper_sorted_pairs(
+    junction_boxes: &Vec<JunctionBox>,
+    metric: Metric,
+) -> Vec<(usize, usize)> {
+    let len = junction_boxes.len();
+    let mut pairs: BTreeMap<usize, BTreeMap<usize, JunctionBoxPair>> =
+        BTreeMap::new();
+    find_distances(junction_boxes, &mut pairs, 0..len, metric);
+    let mut sorted_pairs: Vec<(usize, usize)> = Vec::new();
+    sort_pairs_by_distance(&pairs, &mut sorted_pairs);
+    sorted_pairs
+}
+
+// The circuits, the last two boxes connected and the connections
+// made, as build_circuits leaves them
+//
+#[cfg(test)]
+type BuiltCircuits = (
+    BTreeMap<usize, Circuit>,
+    (usize, usize),
+    Vec<(usize, usize)>,
+);
+
+// Build the circuits from the first upto sorted pairs, without
+// progress reports.
+//
+#[cfg(test)]
+fn testhelper_build_circuits(
+    upto: usize,
+    sorted_pairs: &Vec<(usize, usize)>,
+    jb_count: usize,
+) -> BuiltCircuits {
+    let mut last_two: (usize, usize) = (0, 0);
+    let mut connections: Vec<(usize, usize)> = Vec::new();
+    let circuits = build_circuits(
+        &upto,
+        sorted_pairs,
+        &mut last_two,
+        &mut connections,
+        jb_count,
+        None,
+    );
+    (circuits, last_two, connections)
+}
+
+#[cfg(test)]
+fn string_to_junction_boxes(raw_input: &str) -> Vec<JunctionBox> {
+    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
+    let mut idx: usize = 0;
+    for line in raw_input.split('\n') {
+        let line = line.trim();
+        let Some((label, (x, y, z))) = parse_labeled_point3(line)
+        else {
+            continue;
+        };
+        junction_boxes
+            .push(JunctionBox::new_labeled(x, y, z, idx, label));
+        idx += 1;
+    }
+    junction_boxes
+}
+
+// test clustering with example input
+//
+#[test]
+fn given_example_clusters() {
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+
+    // the example describes 11 circuits after the 10 closest
+    // connections
+    //
+    let sizes = build_clusters(11, &sorted_pairs, len);
+    assert_eq!(DAY08_EXAMPLE_SIZES_AFTER_10.to_vec(), sizes);
+
+    let sizes = build_clusters(1, &sorted_pairs, len);
+    assert_eq!(vec![20], sizes);
+
+    let sizes = build_clusters(len, &sorted_pairs, len);
+    assert_eq!(vec![1; 20], sizes);
+}
+
+// test the connections needed to first build a circuit of a size
+//
+#[test]
+fn given_example_target_size() {
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+
+    // the example joins 162,817,812 to 425,690,689 and then to
+    // 431,825,988, making a circuit of three
+    //
+    assert_eq!(
+        Some(2),
+        connections_to_reach_size(3, &sorted_pairs, len)
+    );
+    assert_eq!(
+        Some(0),
+        connections_to_reach_size(1, &sorted_pairs, len)
+    );
+    assert_eq!(
+        Some(10),
+        connections_to_reach_size(5, &sorted_pairs, len)
+    );
+    assert_eq!(None, connections_to_reach_size(21, &sorted_pairs, len));
+}
+
+// test the direct neighbors of the boxes with example input
+//
+#[test]
+fn given_example_adjacency() {
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+    let (circuits, _, connections) =
+        testhelper_build_circuits(10, &sorted_pairs, len);
+    let neighbors = adjacency(&connections);
+
+    // 162,817,812 is joined to 425,690,689 and then 431,825,988,
+    // which are then joined to each other, closing a triangle
+    //
+    assert_eq!((0, 19), connections[0]);
+    assert_eq!((0, 7), connections[1]);
+    assert_eq!((7, 19), connections[3]);
+    assert_eq!(&BTreeSet::from([7, 19]), &neighbors[&0]);
+    assert_eq!(&BTreeSet::from([0, 19]), &neighbors[&7]);
+    // 425,690,689 is also joined to 346,949,466 later on
+    //
+    assert_eq!(&BTreeSet::from([0, 7, 14]), &neighbors[&19]);
+
+    // every connection appears from both ends, and joins boxes in
+    // the same circuit
+    //
+    let ends: usize = neighbors.values().map(|n| n.len()).sum();
+    assert_eq!(2 * connections.len(), ends);
+    for (id_a, ids_b) in neighbors.iter() {
+        let circuit =
+            circuits.values().find(|c| c.contains(*id_a)).unwrap();
+        assert!(ids_b.iter().all(|id_b| circuit.contains(*id_b)));
+    }
+    // boxes never connected have no entry
+    //
+    let connected: BTreeSet<usize> = circuits
+        .values()
+        .flat_map(|c| c.jbs.iter().copied())
+        .collect();
+    assert_eq!(connected, neighbors.keys().copied().collect());
+}
+
+// test that pairs at equal distances are sorted by box ids
+//
+#[test]
+fn equal_distance_pairs_sort_by_ids() {
+    let junction_boxes = vec![
+        JunctionBox::new(0, 0, 0, 0),
+        JunctionBox::new(1, 0, 0, 1),
+        JunctionBox::new(2, 0, 0, 2),
+        JunctionBox::new(3, 0, 0, 3),
+        JunctionBox::new(0, 1, 0, 4),
+    ];
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+    let expected: Vec<(usize, usize)> =
+        vec![(0, 1), (0, 4), (1, 2), (2, 3), (1, 4), (0, 2), (1, 3)];
+    assert_eq!(expected, sorted_pairs[0..7].to_vec());
+
+    // the first three connections always make the same circuit
+    //
+    let (circuits, last_two, _) =
+        testhelper_build_circuits(3, &sorted_pairs, len);
+    let sorted_circuits = sort_circuits(&circuits);
+    assert_eq!(1, sorted_circuits.len());
+    assert_eq!(4, sorted_circuits[0].1);
+    assert_eq!((1, 2), last_two);
+}
+
+// test that ties between equal-distance pairs go to the boxes
+// earliest in the input, not the boxes with the lowest coordinates
+//
+#[test]
+fn equal_distance_ties_follow_input_order() {
+    // input order 30, 0, 10, 40, 20, 50 along the x axis, so every
+    // neighboring pair is 10 apart
+    //
+    let junction_boxes = vec![
+        JunctionBox::new(30, 0, 0, 0),
+        JunctionBox::new(0, 0, 0, 1),
+        JunctionBox::new(10, 0, 0, 2),
+        JunctionBox::new(40, 0, 0, 3),
+        JunctionBox::new(20, 0, 0, 4),
+        JunctionBox::new(50, 0, 0, 5),
+    ];
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+    let expected: Vec<(usize, usize)> =
+        vec![(0, 3), (0, 4), (1, 2), (2, 4), (3, 5)];
+    assert_eq!(expected, sorted_pairs[0..5].to_vec());
+
+    // the box at 30 came first, so its circuit forms first
+    //
+    let (circuits, _, _) =
+        testhelper_build_circuits(3, &sorted_pairs, len);
+    let members: Vec<Vec<usize>> = circuits
+        .values()
+        .map(|c| c.jbs.iter().copied().collect())
+        .collect();
+    assert_eq!(vec![vec![0, 3, 4], vec![1, 2]], members);
+    let sizes =
+        circuit_sizes_with_singles(&sort_circuits(&circuits), len);
+    assert_eq!(Ok(6), product_of_largest(&sizes, 2));
+
+    // connecting everything ends by joining the box at 50
+    //
+    let (circuits, last_two, _) =
+        testhelper_build_circuits(usize::MAX, &sorted_pairs, len);
+    assert!(verify_single_component(&circuits, len));
+    assert_eq!((3, 5), last_two);
+    let product = junction_boxes[last_two.0].location.x
+        * junction_boxes[last_two.1].location.x;
+    assert_eq!(2000, product);
+}
+
+// test the single component check after connecting everything
+//
+#[test]
+fn verify_single_component_when_connected() {
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+
+    let (circuits, _, _) =
+        testhelper_build_circuits(usize::MAX, &sorted_pairs, len);
+    assert!(verify_single_component(&circuits, len));
+
+    // with the pairs touching box 19 filtered out, box 19 can
+    // never join the circuit
+    //
+    let filtered: Vec<(usize, usize)> = sorted_pairs
+        .iter()
+        .filter(|(a, b)| (19 != *a) && (19 != *b))
+        .copied()
+        .collect();
+    let (circuits, last_two, _) =
+        testhelper_build_circuits(usize::MAX, &filtered, len);
+    assert_eq!(1, circuits.len());
+    assert!(!verify_single_component(&circuits, len));
+    assert_eq!(
+        Err(CircuitError::NotAllConnected(20, 2)),
+        last_connection_product(&junction_boxes, &circuits, last_two)
+    );
+}
+
+// test that adding boxes one at a time gives the same circuits
+// as building them from all the sorted pairs
+//
+#[test]
+fn incremental_matches_batch() {
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+
+    let mut builder = CircuitBuilder::new(Metric::Xyz);
+    for junction_box in string_to_junction_boxes(DAY08_EXAMPLE) {
+        builder.add_box(junction_box);
+    }
+    let mut batch = UnionFind::new(len);
+    for expected_pair in sorted_pairs[0..10].iter() {
+        assert_eq!(Some(*expected_pair), builder.connect_step());
+        batch.union(expected_pair.0, expected_pair.1);
+    }
+    assert_eq!(batch.component_sizes(), builder.circuit_sizes());
+    assert_eq!(
+        DAY08_EXAMPLE_SIZES_AFTER_10.to_vec(),
+        builder.circuit_sizes()
+    );
+
+    // boxes added after some connections are still reachable
+    //
+    let mut builder = CircuitBuilder::new(Metric::Xyz);
+    let mut later: Vec<JunctionBox> =
+        string_to_junction_boxes(DAY08_EXAMPLE);
+    let sooner: Vec<JunctionBox> = later.drain(0..10).collect();
+    for junction_box in sooner {
+        builder.add_box(junction_box);
+    }
+    for _ in 0..5 {
+        builder.connect_step();
+    }
+    for junction_box in later {
+        builder.add_box(junction_box);
+    }
+    while 1 < builder.circuits.component_count() {
+        builder.connect_step().unwrap();
+    }
+    assert_eq!(vec![20], builder.circuit_sizes());
+}
+
+// test that the progress callback counts up to the pairs built
+//
+#[test]
+fn progress_reports_increasing_counts() {
+    use std::cell::RefCell;
+
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+    let pair_count = sorted_pairs.len();
+    assert_eq!(190, pair_count);
+
+    let calls: RefCell<Vec<(usize, usize)>> = RefCell::new(Vec::new());
+    let record = |current: usize, total: usize| {
+        calls.borrow_mut().push((current, total));
+    };
+    let mut last_two: (usize, usize) = (0, 0);
+    let mut connections: Vec<(usize, usize)> = Vec::new();
+    let _circuits = build_circuits(
+        &pair_count,
+        &sorted_pairs,
+        &mut last_two,
+        &mut connections,
+        len,
+        Some(&record),
+    );
+    let calls = calls.into_inner();
+    assert!(1 < calls.len());
+    assert!(calls.iter().all(|(_, total)| pair_count == *total));
+    assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+    assert_eq!((pair_count, pair_count), *calls.last().unwrap());
+
+    assert_eq!("[##   ] 2/5", progress_bar(2, 5, 5));
+}
+
+// test finding the farthest pair in a circuit
+//
+#[test]
+fn circuit_diameter_of_three_boxes() {
+    let junction_boxes = vec![
+        JunctionBox::new(0, 0, 0, 0),
+        JunctionBox::new(3, 4, 0, 1),
+        JunctionBox::new(0, 0, 12, 2),
+    ];
+    let mut circuit = Circuit::new(0);
+    assert_eq!(
+        None,
+        circuit_diameter(&circuit, &junction_boxes, Metric::Xyz)
+    );
+    circuit.insert_box(2);
+    assert_eq!(
+        None,
+        circuit_diameter(&circuit, &junction_boxes, Metric::Xyz)
+    );
+    circuit.insert_box(0);
+    assert_eq!(
+        Some((0, 2, 144)),
+        circuit_diameter(&circuit, &junction_boxes, Metric::Xyz)
+    );
+    circuit.insert_box(1);
+    assert_eq!(
+        Some((1, 2, 169)),
+        circuit_diameter(&circuit, &junction_boxes, Metric::Xyz)
+    );
+}
+
+// test the minimum spanning tree length of a circuit
+//
+#[test]
+fn circuit_mst_length_of_three_boxes() {
+    let junction_boxes = vec![
+        JunctionBox::new(0, 0, 0, 0),
+        JunctionBox::new(3, 4, 0, 1),
+        JunctionBox::new(0, 0, 12, 2),
+        JunctionBox::new(100, 100, 100, 3),
+    ];
+    let mut circuit = Circuit::new(0);
+    assert_eq!(
+        0,
+        circuit_mst_length(&circuit, &junction_boxes, Metric::Xyz)
+    );
+    circuit.insert_box(1);
+    assert_eq!(
+        0,
+        circuit_mst_length(&circuit, &junction_boxes, Metric::Xyz)
+    );
+    circuit.insert_box(2);
+    assert_eq!(
+        169,
+        circuit_mst_length(&circuit, &junction_boxes, Metric::Xyz)
+    );
+    // the 0-1 (25) and 0-2 (144) wires are shorter than the 1-2
+    // (169) wire; box 3 is in another circuit, so it is ignored
+    //
+    circuit.insert_box(0);
+    assert_eq!(
+        25 + 144,
+        circuit_mst_length(&circuit, &junction_boxes, Metric::Xyz)
+    );
+}
+
+// test that recursing and iterating find the same distances
+//
+#[test]
+fn find_distances_threshold_paths_agree() {
+    fn flatten(
+        pairs: &BTreeMap<usize, BTreeMap<usize, JunctionBoxPair>>,
+    ) -> Vec<(usize, usize, usize, usize, u64)> {
+        let mut flat = Vec::new();
+        for (key_a, paired_with) in pairs.iter() {
+            for (key_b, jb) in paired_with.iter() {
+                flat.push((
+                    *key_a,
+                    *key_b,
+                    jb.first_box_id,
+                    jb.second_box_id,
+                    jb.distance,
+                ));
+            }
+        }
+        flat
+    }
+
+    // a pseudo-random arrangement of boxes
+    //
+    let mut seed: i64 = 12345;
+    let mut next_coord = || {
+        seed = (seed * 1103515245 + 12345) % 2147483648;
+        seed % 1000
+    };
+    let len: usize = 60;
+    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
+    for id in 0..len {
+        let (x, y, z) = (next_coord(), next_coord(), next_coord());
+        junction_boxes.push(JunctionBox::new(x, y, z, id));
+    }
+
+    let mut expected = None;
+    for threshold in [0, len - 1, len, usize::MAX] {
+        let mut pairs: BTreeMap<
+            usize,
+            BTreeMap<usize, JunctionBoxPair>,
+        > = BTreeMap::new();
+        find_distances_with_threshold(
+            &junction_boxes,
+            &mut pairs,
+            0..len,
+            Metric::Xyz,
+            threshold,
+        );
+        let flat = flatten(&pairs);
+        assert_eq!(len * (len - 1) / 2, flat.len());
+        match &expected {
+            None => expected = Some(flat),
+            Some(expected) => assert_eq!(expected, &flat),
+        }
+    }
+}
+
+// test asking for the product of more circuits than there are
+//
+#[test]
+fn product_of_more_circuits_than_exist() {
+    let junction_boxes = vec![
+        JunctionBox::new(0, 0, 0, 0),
+        JunctionBox::new(1, 0, 0, 1),
+        JunctionBox::new(100, 0, 0, 2),
+        JunctionBox::new(102, 0, 0, 3),
+    ];
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+    let (circuits, _, _) =
+        testhelper_build_circuits(2, &sorted_pairs, len);
+    let sizes =
+        circuit_sizes_with_singles(&sort_circuits(&circuits), len);
+    assert_eq!(vec![2, 2], sizes);
+    assert_eq!(Ok(4), product_of_largest(&sizes, 2));
+    assert_eq!(
+        Err(CircuitError::TooFewCircuits(3, 2)),
+        product_of_largest(&sizes, 3)
+    );
+
+    // after a single connection, the boxes left over are circuits
+    // of one
+    //
+    let (circuits, _, _) =
+        testhelper_build_circuits(1, &sorted_pairs, len);
+    let sizes =
+        circuit_sizes_with_singles(&sort_circuits(&circuits), len);
+    assert_eq!(vec![2, 1, 1], sizes);
+    assert_eq!(Ok(2), product_of_largest(&sizes, 3));
+}
+
+// test that replaying the recorded connections rebuilds the same
+// circuits
+//
+#[test]
+fn replay_connections() {
+    let junction_boxes = string_to_junction_boxes(DAY08_EXAMPLE);
+    let len = junction_boxes.len();
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xyz);
+
+    let describe = |circuits: &BTreeMap<usize, Circuit>| {
+        circuits
+            .values()
+            .map(|c| c.describe_circuit())
+            .collect::<Vec<String>>()
+    };
+    for upto in [10, usize::MAX] {
+        let (circuits, last_two, connections) =
+            testhelper_build_circuits(upto, &sorted_pairs, len);
+        // connecting everything stops once there is one circuit
+        //
+        if 10 == upto {
+            assert_eq!(10, connections.len());
+        } else {
+            assert!(connections.len() < sorted_pairs.len());
+        }
+        assert_eq!(sorted_pairs[0..connections.len()], connections);
+
+        let (replay_circuits, replay_last_two, replayed) =
+            testhelper_build_circuits(
+                connections.len(),
+                &connections,
+                len,
+            );
+        assert_eq!(connections.len(), replayed.len());
+        assert_eq!(connections, replayed);
+        assert_eq!(describe(&circuits), describe(&replay_circuits));
+        assert_eq!(last_two, replay_last_two);
+    }
+
+    let mut out: Vec<u8> = Vec::new();
+    write_connections(&mut out, &[(0, 19), (0, 7)]).unwrap();
+    assert_eq!("0,19\n0,7\n", String::from_utf8(out).unwrap());
+}
+
+// test that boxes differing only in z pair first in the xy-plane,
+// and that measuring there leaves the coordinates alone
+//
+#[test]
+fn ignore_z_pairs_stacked_boxes_first() {
+    let junction_boxes = vec![
+        JunctionBox::new(0, 0, 0, 0),
+        JunctionBox::new(3, 0, 0, 1),
+        JunctionBox::new(10, 10, 0, 2),
+        JunctionBox::new(10, 10, 50, 3),
+    ];
+    assert_eq!(
+        2500,
+        junction_boxes[2]
+            .distance_from(&junction_boxes[3], Metric::Xyz)
+    );
+    assert_eq!(
+        0,
+        junction_boxes[2].distance_from(&junction_boxes[3], Metric::Xy)
+    );
+    assert_eq!(
+        9,
+        junction_boxes[0].distance_from(&junction_boxes[1], Metric::Xy)
+    );
+    let sorted_pairs =
+        testhelper_sorted_pairs(&junction_boxes, Metric::Xy);
+    assert_eq!(vec![(2, 3), (0, 1)], sorted_pairs[0..2].to_vec());
+    assert_eq!("(10,10,50)", junction_boxes[3].describe_coords());
+    assert!(
+        describe_connection(&junction_boxes, 2, 3, Metric::Xy)
+            .contains("squared distance 0,")
+    );
+
+    let mut builder = CircuitBuilder::new(Metric::Xy);
+    for junction_box in junction_boxes {
+        builder.add_box(junction_box);
+    }
+    assert_eq!(Some((2, 3)), builder.connect_step());
+    assert_eq!(Some((0, 1)), builder.connect_step());
+}
+
+// test that labeled and unlabeled lines can be mixed
+//
+#[test]
+fn labeled_and_unlabeled_boxes() {
+    let raw_input = "A: 162,817,812
+57,618,57
+  hub : 906,360,560
+592,479,940";
+    let junction_boxes = string_to_junction_boxes(raw_input);
+    assert_eq!(4, junction_boxes.len());
+    let labels: Vec<Option<&str>> = junction_boxes
+        .iter()
+        .map(|jb| jb.label.as_deref())
+        .collect();
+    assert_eq!(vec![Some("A"), None, Some("hub"), None], labels);
+    let ids: Vec<usize> =
+        junction_boxes.iter().map(|jb| jb.id).collect();
+    assert_eq!(vec![0, 1, 2, 3], ids);
+    assert_eq!("A (162,817,812)", junction_boxes[0].describe_coords());
+    assert_eq!("(57,618,57)", junction_boxes[1].describe_coords());
+    assert_eq!(
+        "2-3 hub (906,360,560) to (592,479,940): squared distance 257157, length 507.106",
+        describe_connection(&junction_boxes, 2, 3, Metric::Xyz)
+    );
 }
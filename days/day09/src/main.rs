@@ -1,12 +1,14 @@
 use ::std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::ops::Range;
 use std::path::PathBuf;
 use std::time::Instant;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
 use regex::Regex;
 
 /// Given input file containing the coordinates of red tiles,
@@ -17,24 +19,40 @@ struct Cli {
     /// Whether to apply the green tile specifications
     #[arg(long = "consider-green-tiles")]
     with_green_tiles: bool,
+    /// Write the outlined and filled grids to this file
+    /// (only used with --consider-green-tiles)
+    #[arg(long = "render-file", alias = "render")]
+    render_path: Option<PathBuf>,
+    /// Write the interior (filled) tiles to this file, one `x,y`
+    /// per line (only used with --consider-green-tiles)
+    #[arg(long = "dump-interior")]
+    interior_path: Option<PathBuf>,
+    /// Let a filled rectangle cover the red and green tiles of
+    /// the outline, not just the tiles filled in inside it
+    /// (only used with --consider-green-tiles)
+    #[arg(long = "include-boundary")]
+    include_boundary: bool,
+    /// Print a summary of the red tiles and their outline,
+    /// then exit without solving
+    #[arg(long = "stats")]
+    stats: bool,
+    /// Fill the outline by this rule instead of by marking the
+    /// inside of each corner; either rule can fill a
+    /// self-overlapping outline
+    /// (only used with --consider-green-tiles)
+    #[arg(long = "fill-rule", value_enum)]
+    fill_rule: Option<FillRule>,
     /// The path to the file containing red tile coordinates
     path: PathBuf,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Point {
     x: u64, // column
     y: u64, // row
 }
 
 impl Point {
-    fn clone(&self) -> Self {
-        Point {
-            x: self.x,
-            y: self.y,
-        }
-    }
-
     fn new(x: u64, y: u64) -> Self {
         Point { x: x, y: y }
     }
@@ -100,7 +118,41 @@ enum InsideIs {
     Unknown,
 }
 
-#[derive(Debug)]
+// How to decide whether a tile is inside the outline, from the
+// winding number: the sum of the directions of the vertical edges
+// crossed moving left from the tile, down counting +1 and up -1.
+//
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum FillRule {
+    // inside if an odd number of edges are crossed
+    EvenOdd,
+    // inside if the winding number is not zero
+    #[value(name = "nonzero")]
+    NonZero,
+}
+
+impl FillRule {
+    fn is_inside(&self, crossings: u64, winding: i64) -> bool {
+        match self {
+            FillRule::EvenOdd => 1 == (crossings % 2),
+            FillRule::NonZero => 0 != winding,
+        }
+    }
+}
+
+// A vertical edge of the outline, covering rows y_lo..y_hi
+// (the lower end excluded) in column x. The winding is +1 if
+// the outline moves down along the edge and -1 if it moves up.
+//
+#[derive(Debug, Clone, Copy)]
+struct VerticalEdge {
+    x: u64,
+    y_lo: u64,
+    y_hi: u64,
+    winding: i64,
+}
+
+#[derive(Debug, Clone)]
 struct Tile {
     loc: Point,
     color: TileColor,
@@ -126,8 +178,15 @@ impl Tile {
     }
 }
 
+#[derive(Clone)]
 struct TileGrid {
     tiles: BTreeMap<u64, BTreeMap<u64, Tile>>,
+    // the vertical edges, as connected, for fill_with_rule
+    vertical_edges: Vec<VerticalEdge>,
+    // the red tiles, in order around the outline, and the rule the
+    // grid was filled by, for add_red_and_refill_region
+    outline: Vec<Point>,
+    fill_rule: Option<FillRule>,
     min_x: u64,
     min_y: u64,
     max_x: u64,
@@ -139,6 +198,9 @@ impl TileGrid {
         let grid: BTreeMap<u64, BTreeMap<u64, Tile>> = BTreeMap::new();
         TileGrid {
             tiles: grid,
+            vertical_edges: Vec::new(),
+            outline: Vec::new(),
+            fill_rule: None,
             min_x: u64::MAX,
             min_y: u64::MAX,
             max_x: 0,
@@ -146,6 +208,101 @@ impl TileGrid {
         }
     }
 
+    // Build the grid from the red tiles, in order around the
+    // outline: connect them with green tiles and fill in the loop.
+    //
+    // Returns an error if the red tiles do not outline a closed,
+    // simple loop.
+    //
+    #[allow(dead_code)]
+    fn from_closed_polygon(
+        points: &[Point],
+    ) -> Result<Self, OutlineError> {
+        check_outline(points, None)?;
+        let mut grid = TileGrid::outlined(points);
+        grid.fill(points, None);
+        Ok(grid)
+    }
+
+    // Build the grid from the red tiles, in order around the
+    // outline: connect them with green tiles and fill in by the
+    // rule. Unlike from_closed_polygon, the outline may cross or
+    // overlap itself.
+    //
+    // Returns an error if the red tiles do not outline a closed
+    // loop.
+    //
+    #[allow(dead_code)]
+    fn from_outline_with_rule(
+        points: &[Point],
+        rule: FillRule,
+    ) -> Result<Self, OutlineError> {
+        check_outline(points, Some(rule))?;
+        let mut grid = TileGrid::outlined(points);
+        grid.fill(points, Some(rule));
+        Ok(grid)
+    }
+
+    // Build the grid from the red tiles, in order around the
+    // outline, connecting each to the next (and the last to the
+    // first) with green tiles. Nothing is filled in yet.
+    //
+    fn outlined(points: &[Point]) -> Self {
+        let mut grid = TileGrid::new();
+        grid.outline = points.to_vec();
+        for p in points {
+            grid.insert_red_tile(p);
+        }
+        let len = points.len();
+        for a in 0..len {
+            let b = (a + 1) % len;
+            grid.connect_red_tiles_with_green_tiles(
+                &points[a], &points[b],
+            );
+        }
+        grid
+    }
+
+    // Fill in the outline made by `outlined` from the same red
+    // tiles: by the rule if one is given, otherwise by marking
+    // the inside of each red tile and filling in the loop.
+    //
+    fn fill(&mut self, points: &[Point], rule: Option<FillRule>) {
+        if let Some(rule) = rule {
+            self.fill_with_rule(rule);
+            return;
+        }
+        let len = points.len();
+        for a in 0..len {
+            let b = (a + 1) % len;
+            self.mark_red_tiles_with_inside_direction(
+                &points[a], &points[b],
+            );
+        }
+        self.fill_in_loops();
+    }
+
+    // Returns true if the grid contains no tiles
+    //
+    fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    // Returns the upper left and lower right corners of the
+    // smallest rectangle containing all the tiles, or None if
+    // the grid is empty.
+    //
+    fn bounding_box(&self) -> Option<(Point, Point)> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((
+                Point::new(self.min_x, self.min_y),
+                Point::new(self.max_x, self.max_y),
+            ))
+        }
+    }
+
     fn insert_green_tile(&mut self, loc: &Point) {
         self.insert_tile(loc, TileColor::Green);
     }
@@ -173,7 +330,7 @@ impl TileGrid {
         }
         let row = self.tiles.get_mut(&loc.x).unwrap();
         if !row.contains_key(&loc.y) {
-            let tile = Tile::new(loc.clone(), color);
+            let tile = Tile::new(*loc, color);
             row.insert(loc.y, tile);
             self.min_x = self.min_x.min(loc.x);
             self.min_y = self.min_y.min(loc.y);
@@ -597,6 +754,14 @@ impl TileGrid {
             // draw up or down
             //
             let x = a.x;
+            if a.y != b.y {
+                self.vertical_edges.push(VerticalEdge {
+                    x,
+                    y_lo: a.y.min(b.y),
+                    y_hi: a.y.max(b.y),
+                    winding: if a.y < b.y { 1 } else { -1 },
+                });
+            }
             if a.y <= b.y {
                 let start = a.y + 1;
                 let end = b.y;
@@ -867,6 +1032,229 @@ impl TileGrid {
         self.fill_if_neighbors();
     }
 
+    // Fill in the tiles inside the outline by the rule, sweeping
+    // each row from the left and adding up the vertical edges
+    // crossed. Tiles of the outline are never filled. The ray
+    // along a row counts an edge only if the row is in y_lo..y_hi,
+    // as though it ran just below the row's center, so running
+    // along a horizontal edge or through a corner counts right.
+    //
+    fn fill_with_rule(&mut self, rule: FillRule) {
+        self.fill_rule = Some(rule);
+        if self.is_empty() {
+            return;
+        }
+        self.fill_region_with_rule(
+            rule, self.min_x, self.max_x, self.min_y, self.max_y,
+        );
+    }
+
+    // Fill by the rule as fill_with_rule does, but only the tiles
+    // in x_lo..=x_hi, y_lo..=y_hi. The edges left of the region
+    // still count toward the crossings.
+    //
+    fn fill_region_with_rule(
+        &mut self,
+        rule: FillRule,
+        x_lo: u64,
+        x_hi: u64,
+        y_lo: u64,
+        y_hi: u64,
+    ) {
+        for y in y_lo..=y_hi {
+            let mut edges: Vec<VerticalEdge> = self
+                .vertical_edges
+                .iter()
+                .filter(|e| (e.y_lo <= y) && (y < e.y_hi))
+                .copied()
+                .collect();
+            edges.sort_by_key(|e| e.x);
+            let mut next_edge: usize = 0;
+            let mut crossings: u64 = 0;
+            let mut winding: i64 = 0;
+            for x in x_lo..=x_hi {
+                while (next_edge < edges.len())
+                    && (edges[next_edge].x < x)
+                {
+                    crossings += 1;
+                    winding += edges[next_edge].winding;
+                    next_edge += 1;
+                }
+                if self.is_color_other(x, y)
+                    && rule.is_inside(crossings, winding)
+                {
+                    let loc = Point::new(x, y);
+                    self.insert_green_fill_tile(&loc);
+                }
+            }
+        }
+    }
+
+    // Add a red tile to the end of the outline, so that it connects
+    // the last red tile to the first, and refill only where the
+    // change can reach. The grid must have been built from its
+    // outline, by from_outline_with_rule or from_closed_polygon.
+    //
+    // Only the edges between the last tile, the new tile and the
+    // first tile change, so no row outside their span changes. When
+    // the outline is closed both before and after, the old and new
+    // paths between the last and first tiles cross each row the same
+    // way, so nothing right of the three tiles changes either. A
+    // diagonal connection is left undrawn until the outline closes,
+    // and then the region runs to the right edge of the grid.
+    //
+    #[allow(dead_code)]
+    fn add_red_and_refill_region(&mut self, p: &Point) {
+        let (first, last) =
+            match (self.outline.first(), self.outline.last()) {
+                (Some(first), Some(last)) => (*first, *last),
+                _ => {
+                    self.outline.push(*p);
+                    self.insert_red_tile(p);
+                    return;
+                }
+            };
+        let was_closed = is_axis_aligned(&last, &first);
+        let now_closed = is_axis_aligned(p, &first);
+        let x_lo = first.x.min(last.x).min(p.x);
+        let y_lo = first.y.min(last.y).min(p.y);
+        let y_hi = first.y.max(last.y).max(p.y);
+
+        // clear the fill in the region and take out the old closing
+        // connection, except where another connection runs
+        //
+        let rule = self.fill_rule.unwrap_or(FillRule::EvenOdd);
+        let x_hi = if was_closed && now_closed {
+            first.x.max(last.x).max(p.x)
+        } else {
+            self.max_x.max(p.x)
+        };
+        for x in x_lo..=x_hi {
+            for y in y_lo..=y_hi {
+                if self.is_color_green_fill(x, y) {
+                    self.remove_tile(x, y);
+                }
+            }
+        }
+        if was_closed {
+            self.remove_connection(&last, &first);
+        }
+        if !self.is_color_red(p.x, p.y) {
+            self.remove_tile(p.x, p.y);
+        }
+
+        // connect the new tile and refill
+        //
+        self.outline.push(*p);
+        self.insert_red_tile(p);
+        if is_axis_aligned(&last, p) {
+            self.connect_red_tiles_with_green_tiles(&last, p);
+        }
+        if now_closed {
+            self.connect_red_tiles_with_green_tiles(p, &first);
+        }
+        self.fill_region_with_rule(rule, x_lo, x_hi, y_lo, y_hi);
+    }
+
+    // Take out the green tiles and the vertical edge connecting a to
+    // b, keeping any tile that another connection of the outline
+    // (the closing one excluded) runs through.
+    //
+    fn remove_connection(&mut self, a: &Point, b: &Point) {
+        if a.x == b.x
+            && a.y != b.y
+            && let Some(i) = self.vertical_edges.iter().rposition(|e| {
+                (e.x == a.x)
+                    && (e.y_lo == a.y.min(b.y))
+                    && (e.y_hi == a.y.max(b.y))
+            })
+        {
+            self.vertical_edges.remove(i);
+        }
+        for x in a.x.min(b.x)..=a.x.max(b.x) {
+            for y in a.y.min(b.y)..=a.y.max(b.y) {
+                let loc = Point::new(x, y);
+                let kept = self.outline.windows(2).any(|w| {
+                    is_axis_aligned(&w[0], &w[1])
+                        && within_segment(&w[0], &w[1], &loc)
+                });
+                if self.is_color_green(x, y) && !kept {
+                    self.remove_tile(x, y);
+                }
+            }
+        }
+    }
+
+    fn remove_tile(&mut self, x: u64, y: u64) {
+        if let Some(row) = self.tiles.get_mut(&x) {
+            if row.remove(&y).is_none() {
+                return;
+            }
+            if row.is_empty() {
+                self.tiles.remove(&x);
+            }
+            if (self.min_x == x)
+                || (self.max_x == x)
+                || (self.min_y == y)
+                || (self.max_y == y)
+            {
+                self.recompute_bounds();
+            }
+        }
+    }
+
+    // Recompute the bounds from the tiles, after a tile on the
+    // bounding box has been removed.
+    //
+    fn recompute_bounds(&mut self) {
+        self.min_x = u64::MAX;
+        self.min_y = u64::MAX;
+        self.max_x = 0;
+        self.max_y = 0;
+        for (x, row) in self.tiles.iter() {
+            if let (Some(y_lo), Some(y_hi)) =
+                (row.keys().next(), row.keys().next_back())
+            {
+                self.min_x = self.min_x.min(*x);
+                self.max_x = self.max_x.max(*x);
+                self.min_y = self.min_y.min(*y_lo);
+                self.max_y = self.max_y.max(*y_hi);
+            }
+        }
+    }
+
+    // The number of red, green and green fill tiles in the grid
+    //
+    #[cfg(test)]
+    fn cell_census(&self) -> (u64, u64, u64) {
+        let mut census: (u64, u64, u64) = (0, 0, 0);
+        for row in self.tiles.values() {
+            for tile in row.values() {
+                match tile.color {
+                    TileColor::Red => census.0 += 1,
+                    TileColor::Green => census.1 += 1,
+                    TileColor::GreenFill => census.2 += 1,
+                    TileColor::Other => {}
+                }
+            }
+        }
+        census
+    }
+
+    // Returns true if filling the grid again, the way it was first
+    // filled, changes nothing; a fill that keeps growing is wrong.
+    //
+    #[cfg(test)]
+    fn fill_is_stable(&self) -> bool {
+        let mut refilled = self.clone();
+        match self.fill_rule {
+            Some(rule) => refilled.fill_with_rule(rule),
+            None => refilled.fill_in_loops(),
+        }
+        (self.cell_census() == refilled.cell_census())
+            && (self.render() == refilled.render())
+    }
+
     fn is_outside(&self, x: u64, y: u64) -> bool {
         match self.get_color(x, y) {
             TileColor::Other => {
@@ -1010,7 +1398,19 @@ impl TileGrid {
         row.get_mut(&y).unwrap().set_inside_direction(idir);
     }
 
+    #[allow(dead_code)]
     fn display_grid(&self) {
+        print!("{}", self.render());
+    }
+
+    // Produce the ASCII rendering of the grid, one line per row.
+    // An empty grid renders as an empty string.
+    //
+    fn render(&self) -> String {
+        let mut rendered = String::new();
+        if self.is_empty() {
+            return rendered;
+        }
         for y in 0..=self.max_y {
             let mut disp_row: Vec<String> = Vec::new();
             for x in 0..=self.max_x {
@@ -1030,11 +1430,69 @@ impl TileGrid {
                     }
                 }
             }
-            println!("{}", disp_row.join(""));
+            rendered.push_str(&disp_row.join(""));
+            rendered.push('\n');
         }
+        rendered
     }
 
-    fn is_filled(&self, a: &Point, b: &Point) -> bool {
+    // Write the ASCII rendering of the grid to the writer.
+    //
+    fn write_render<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(self.render().as_bytes())
+    }
+
+    // Write the tiles filled in as inside the outline to the
+    // writer, one `x,y` per line, ordered by x and then by y.
+    //
+    fn write_interior<W: Write>(
+        &self,
+        w: &mut W,
+    ) -> std::io::Result<()> {
+        for row in self.tiles.values() {
+            for tile in row.values() {
+                if let TileColor::GreenFill = tile.color {
+                    writeln!(w, "{},{}", tile.loc.x, tile.loc.y)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The number of tiles filled in as inside the outline
+    //
+    fn interior_area(&self) -> u64 {
+        let mut area: u64 = 0;
+        for row in self.tiles.values() {
+            for tile in row.values() {
+                if let TileColor::GreenFill = tile.color {
+                    area += 1;
+                }
+            }
+        }
+        area
+    }
+
+    // The number of tiles filled in as inside the outline within
+    // the rectangle with opposite corners a and b, corners and
+    // edges included. Builds a prefix sum of the grid; to make
+    // many queries, build a FilledPrefixSum once and use its
+    // filled_in_rect instead.
+    //
+    #[allow(dead_code)]
+    fn filled_in_rect(&self, a: &Point, b: &Point) -> u64 {
+        FilledPrefixSum::new(self, false).filled_in_rect(a, b)
+    }
+
+    // sequential reference for FilledPrefixSum::is_filled
+    //
+    #[allow(dead_code)]
+    fn is_filled(
+        &self,
+        a: &Point,
+        b: &Point,
+        include_boundary: bool,
+    ) -> bool {
         let mut ul: Point = Point::new(0, 0);
         let mut br: Point = Point::new(0, 0);
         if a.x < b.x && a.y < b.y {
@@ -1058,6 +1516,8 @@ impl TileGrid {
             for y in y_s..y_e {
                 match self.get_color(x, y) {
                     TileColor::GreenFill => {}
+                    TileColor::Red | TileColor::Green
+                        if include_boundary => {}
                     _ => {
                         return false;
                     }
@@ -1067,11 +1527,15 @@ impl TileGrid {
         true
     }
 
+    // sequential reference for find_max_filled_area_parallel
+    //
+    #[allow(dead_code)]
     fn find_max_filled_area(
         &self,
         max_area: &mut u64,
         points: &Vec<Point>,
         rng: Range<usize>,
+        include_boundary: bool,
     ) {
         let id_a: usize = rng.start;
         let end: usize = rng.end;
@@ -1079,11 +1543,16 @@ impl TileGrid {
             return;
         }
         let start = id_a + 1;
-        self.find_max_filled_area(max_area, points, start..end);
+        self.find_max_filled_area(
+            max_area,
+            points,
+            start..end,
+            include_boundary,
+        );
         let point_a = points.get(id_a).unwrap();
         for id_b in start..end {
             let point_b = points.get(id_b).unwrap();
-            if self.is_filled(point_a, point_b) {
+            if self.is_filled(point_a, point_b, include_boundary) {
                 let area = point_a.area_with(point_b);
                 if area > *max_area {
                     *max_area = area
@@ -1091,104 +1560,665 @@ impl TileGrid {
             }
         }
     }
-}
 
-fn find_max_area(
-    max_area: &mut u64,
-    points: &Vec<Point>,
-    rng: Range<usize>,
-) {
-    let id_a: usize = rng.start;
-    let end: usize = rng.end;
-    if 1 >= (end - id_a) {
-        return;
-    }
-    let start = id_a + 1;
-    find_max_area(max_area, points, start..end);
-    let point_a = points.get(id_a).unwrap();
-    for id_b in start..end {
-        let point_b = points.get(id_b).unwrap();
-        let area = point_a.area_with(point_b);
-        if area > *max_area {
-            *max_area = area
+    // Find every filled rectangle with red tiles as opposite
+    // corners that does not lie within another such rectangle.
+    // Rectangles only one tile wide or high have no area, so are
+    // left out.
+    //
+    // Returns (upper left, bottom right, area) for each maximal
+    // rectangle, ordered by upper left and then bottom right.
+    //
+    #[allow(dead_code)]
+    fn maximal_filled_rectangles(
+        &self,
+        points: &[Point],
+        include_boundary: bool,
+    ) -> Vec<(Point, Point, u64)> {
+        let mut filled: Vec<(Point, Point, u64)> = Vec::new();
+        for (id_a, point_a) in points.iter().enumerate() {
+            for point_b in points[id_a + 1..].iter() {
+                let area = point_a.area_with(point_b);
+                if (0 == area)
+                    || !self.is_filled(
+                        point_a,
+                        point_b,
+                        include_boundary,
+                    )
+                {
+                    continue;
+                }
+                let ul = Point::new(
+                    point_a.x.min(point_b.x),
+                    point_a.y.min(point_b.y),
+                );
+                let br = Point::new(
+                    point_a.x.max(point_b.x),
+                    point_a.y.max(point_b.y),
+                );
+                filled.push((ul, br, area));
+            }
+        }
+        filled.sort_by_key(|(ul, br, _)| (ul.x, ul.y, br.x, br.y));
+        filled.dedup_by_key(|(ul, br, _)| (ul.x, ul.y, br.x, br.y));
+        let mut maximal: Vec<(Point, Point, u64)> = Vec::new();
+        for (idx, (ul, br, area)) in filled.iter().enumerate() {
+            let within_another = filled.iter().enumerate().any(
+                |(other, (o_ul, o_br, _))| {
+                    (other != idx)
+                        && rectangle_within(ul, br, o_ul, o_br)
+                },
+            );
+            if !within_another {
+                maximal.push((*ul, *br, *area));
+            }
         }
+        maximal
     }
 }
 
-fn file_to_points(f: File) -> Vec<Point> {
-    let rdr = BufReader::new(f);
-    let lines = rdr.lines();
-    let mut points: Vec<Point> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*$").unwrap();
-    let mut line_num: usize = 0;
-    for line in lines {
-        line_num += 1;
-        let line = line.unwrap();
-        let line = line.trim();
-        if 0 == line.len() {
-            continue;
+// Returns true if the rectangle from upper left ul to bottom right
+// br lies within the rectangle from outer_ul to outer_br
+//
+fn rectangle_within(
+    ul: &Point,
+    br: &Point,
+    outer_ul: &Point,
+    outer_br: &Point,
+) -> bool {
+    (outer_ul.x <= ul.x)
+        && (outer_ul.y <= ul.y)
+        && (br.x <= outer_br.x)
+        && (br.y <= outer_br.y)
+}
+
+// Summed-area table of the filled tiles within the grid's bounding
+// box, so whether a rectangle is entirely filled can be answered
+// in constant time. It holds no references to the grid, so it can
+// be shared across threads. When built to include the boundary,
+// the red and green tiles of the outline count as filled.
+//
+struct FilledPrefixSum {
+    min_x: u64,
+    min_y: u64,
+    max_x: u64,
+    max_y: u64,
+    // row stride of sums
+    stride: usize,
+    // sums[y * stride + x] is the number of filled tiles with
+    // column < min_x + x and row < min_y + y
+    sums: Vec<u64>,
+}
+
+impl FilledPrefixSum {
+    fn new(grid: &TileGrid, include_boundary: bool) -> Self {
+        let (ul, br) = match grid.bounding_box() {
+            None => (Point::new(1, 1), Point::new(0, 0)),
+            Some(corners) => corners,
+        };
+        let width = (br.x + 1 - ul.x) as usize;
+        let height = (br.y + 1 - ul.y) as usize;
+        let stride = width + 1;
+        let mut sums: Vec<u64> = vec![0; stride * (height + 1)];
+        for j in 0..height {
+            let mut row_count: u64 = 0;
+            for i in 0..width {
+                let x = ul.x + i as u64;
+                let y = ul.y + j as u64;
+                if grid.is_color_green_fill(x, y)
+                    || (include_boundary
+                        && (grid.is_color_red(x, y)
+                            || grid.is_color_green(x, y)))
+                {
+                    row_count += 1;
+                }
+                sums[(j + 1) * stride + i + 1] =
+                    sums[j * stride + i + 1] + row_count;
+            }
         }
-        if !re_coord.is_match(&line) {
-            println!(
-                "*** FAILED *** to match line {}: '{}'",
-                line_num, line
-            );
-            continue;
+        FilledPrefixSum {
+            min_x: ul.x,
+            min_y: ul.y,
+            max_x: br.x,
+            max_y: br.y,
+            stride,
+            sums,
         }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<u64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<u64>().unwrap();
-        let p = Point::new(x, y);
-        points.push(p);
     }
-    points
-}
 
-fn string_to_points(raw_input: String) -> Vec<Point> {
-    let mut points: Vec<Point> = Vec::new();
-    let re_coord =
-        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*$").unwrap();
-    let input = raw_input.as_str();
-    let lines = input.split('\n');
-    let mut line_num: usize = 0;
-    for line in lines {
-        line_num += 1;
-        let line = line.trim();
-        if 0 == line.len() {
-            continue;
+    // The number of filled tiles in columns x_s..x_e and
+    // rows y_s..y_e, which must lie within the bounding box
+    //
+    fn filled_count(
+        &self,
+        x_s: u64,
+        x_e: u64,
+        y_s: u64,
+        y_e: u64,
+    ) -> u64 {
+        let i_s = (x_s - self.min_x) as usize;
+        let i_e = (x_e - self.min_x) as usize;
+        let j_s = (y_s - self.min_y) as usize;
+        let j_e = (y_e - self.min_y) as usize;
+        self.sums[j_e * self.stride + i_e]
+            + self.sums[j_s * self.stride + i_s]
+            - self.sums[j_s * self.stride + i_e]
+            - self.sums[j_e * self.stride + i_s]
+    }
+
+    // The number of filled tiles within the rectangle with
+    // opposite corners a and b, corners and edges included. The
+    // part of the rectangle beyond the bounding box has none.
+    //
+    fn filled_in_rect(&self, a: &Point, b: &Point) -> u64 {
+        let x_s = a.x.min(b.x).max(self.min_x);
+        let x_e = (a.x.max(b.x) + 1).min(self.max_x + 1);
+        let y_s = a.y.min(b.y).max(self.min_y);
+        let y_e = (a.y.max(b.y) + 1).min(self.max_y + 1);
+        if (x_s >= x_e) || (y_s >= y_e) {
+            return 0;
         }
-        if !re_coord.is_match(&line) {
-            println!(
-                "*** FAILED *** to match line {}: '{}'",
-                line_num, line
-            );
-            continue;
+        self.filled_count(x_s, x_e, y_s, y_e)
+    }
+
+    // Returns true if every tile strictly inside the rectangle
+    // with opposite corners a and b is filled; otherwise false.
+    // Agrees with TileGrid::is_filled.
+    //
+    fn is_filled(&self, a: &Point, b: &Point) -> bool {
+        if (a.x == b.x) || (a.y == b.y) {
+            return true;
         }
-        let coords = re_coord.captures(&line).unwrap();
-        let xs = coords.get(1).unwrap().as_str();
-        let x = xs.parse::<u64>().unwrap();
-        let ys = coords.get(2).unwrap().as_str();
-        let y = ys.parse::<u64>().unwrap();
-        let p = Point::new(x, y);
-        points.push(p);
+        let x_s = a.x.min(b.x) + 1;
+        let x_e = a.x.max(b.x);
+        let y_s = a.y.min(b.y) + 1;
+        let y_e = a.y.max(b.y);
+        if (x_s >= x_e) || (y_s >= y_e) {
+            return true;
+        }
+        if (x_s < self.min_x)
+            || (x_e > self.max_x + 1)
+            || (y_s < self.min_y)
+            || (y_e > self.max_y + 1)
+        {
+            return false;
+        }
+        let expected = (x_e - x_s) * (y_e - y_s);
+        expected == self.filled_count(x_s, x_e, y_s, y_e)
     }
-    points
 }
 
-// Binary crate entry point
+// Find the largest area of a filled rectangle with red tiles as
+// opposite corners, searching the pairs in parallel.
 //
-fn main() -> Result<()> {
-    let args = Cli::parse();
-    let mut upto: usize = 10;
-    let path = &args.path;
-    let consider_green_tiles = &args.with_green_tiles;
+fn find_max_filled_area_parallel(
+    filled: &FilledPrefixSum,
+    points: &[Point],
+) -> u64 {
+    let len = points.len();
+    (0..len)
+        .into_par_iter()
+        .map(|id_a| {
+            let point_a = &points[id_a];
+            points[id_a + 1..]
+                .iter()
+                .filter(|point_b| filled.is_filled(point_a, point_b))
+                .map(|point_b| point_a.area_with(point_b))
+                .max()
+                .unwrap_or(0)
+        })
+        .max()
+        .unwrap_or(0)
+}
 
-    let f = File::open(path).with_context(|| {
-        format!("Could not open `{}`", path.display())
-    })?;
+// The sign of the turn from a to b to c: positive if
+// counterclockwise, negative if clockwise, zero if collinear
+//
+fn turn_direction(a: &Point, b: &Point, c: &Point) -> i64 {
+    let (ax, ay) = (a.x as i64, a.y as i64);
+    let (bx, by) = (b.x as i64, b.y as i64);
+    let (cx, cy) = (c.x as i64, c.y as i64);
+    ((bx - ax) * (cy - ay) - (by - ay) * (cx - ax)).signum()
+}
+
+// Returns true if a and b share a row or a column, so that green
+// tiles can connect them.
+//
+fn is_axis_aligned(a: &Point, b: &Point) -> bool {
+    (a.x == b.x) || (a.y == b.y)
+}
+
+// Returns true if c lies within the bounding box of segment a-b;
+// only meaningful when a, b and c are collinear.
+//
+fn within_segment(a: &Point, b: &Point, c: &Point) -> bool {
+    (a.x.min(b.x) <= c.x)
+        && (c.x <= a.x.max(b.x))
+        && (a.y.min(b.y) <= c.y)
+        && (c.y <= a.y.max(b.y))
+}
+
+// Returns true if the segments p1-p2 and q1-q2 share any point,
+// whether they cross, touch or overlap.
+//
+fn segments_intersect(
+    p1: &Point,
+    p2: &Point,
+    q1: &Point,
+    q2: &Point,
+) -> bool {
+    let d1 = turn_direction(q1, q2, p1);
+    let d2 = turn_direction(q1, q2, p2);
+    let d3 = turn_direction(p1, p2, q1);
+    let d4 = turn_direction(p1, p2, q2);
+    if (d1 * d2 < 0) && (d3 * d4 < 0) {
+        return true;
+    }
+    ((0 == d1) && within_segment(q1, q2, p1))
+        || ((0 == d2) && within_segment(q1, q2, p2))
+        || ((0 == d3) && within_segment(p1, p2, q1))
+        || ((0 == d4) && within_segment(p1, p2, q2))
+}
+
+// Returns true if the closed outline through the points, in order,
+// is simple; false if any two non-adjacent segments cross, touch
+// or overlap. Filling assumes a simple outline.
+//
+fn outline_is_simple(points: &[Point]) -> bool {
+    let len = points.len();
+    if 4 > len {
+        return true;
+    }
+    for i in 0..len {
+        let p1 = &points[i];
+        let p2 = &points[(i + 1) % len];
+        // skip the segment itself and its successor, which shares
+        // an end point; the last segment is adjacent to the first
+        //
+        for j in (i + 2)..len {
+            if (0 == i) && (len - 1 == j) {
+                continue;
+            }
+            let q1 = &points[j];
+            let q2 = &points[(j + 1) % len];
+            if segments_intersect(p1, p2, q1, q2) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// Whether the red tiles, in order, outline a loop that can be
+// filled
+//
+#[derive(Debug, PartialEq)]
+struct OutlineValidation {
+    // every tile, including the last back to the first, is joined
+    // to the next by a horizontal or vertical line
+    closed: bool,
+    // no two non-adjacent segments cross, touch or overlap
+    simple: bool,
+}
+
+fn validate_outline(points: &[Point]) -> OutlineValidation {
+    let len = points.len();
+    let mut closed = 4 <= len;
+    for i in 0..len {
+        let a = &points[i];
+        let b = &points[(i + 1) % len];
+        if (a.x == b.x) == (a.y == b.y) {
+            // diagonal, or the same tile twice
+            closed = false;
+        }
+    }
+    OutlineValidation {
+        closed,
+        simple: outline_is_simple(points),
+    }
+}
+
+// Why the red tiles cannot be made into a filled grid
+//
+#[derive(Debug, PartialEq)]
+enum OutlineError {
+    NotClosed,
+    NotSimple,
+}
+
+impl fmt::Display for OutlineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutlineError::NotClosed => {
+                write!(
+                    f,
+                    "The red tiles are not joined into a closed loop by horizontal and vertical lines"
+                )
+            }
+            OutlineError::NotSimple => {
+                write!(
+                    f,
+                    "The red tiles outline a self-intersecting loop"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OutlineError {}
+
+// The number of steps around the outline, including the step
+// from the last tile back to the first
+//
+fn outline_perimeter(points: &[Point]) -> u64 {
+    let len = points.len();
+    let mut perimeter: u64 = 0;
+    for i in 0..len {
+        let a = &points[i];
+        let b = &points[(i + 1) % len];
+        perimeter += a.x.abs_diff(b.x) + a.y.abs_diff(b.y);
+    }
+    perimeter
+}
+
+// The area enclosed by the outline, by the shoelace formula,
+// measured between tile centers. Adding the tiles the outline
+// cuts through, by Pick's theorem, gives the number of tiles on
+// or inside the outline: area + perimeter / 2 + 1.
+//
+#[allow(dead_code)]
+fn polygon_area(points: &[Point]) -> u64 {
+    twice_signed_area(points).unsigned_abs() / 2
+}
+
+// Twice the area enclosed by the outline, by the shoelace formula:
+// positive if the outline turns counterclockwise, in the sense of
+// turn_direction, and negative if clockwise.
+//
+fn twice_signed_area(points: &[Point]) -> i64 {
+    let len = points.len();
+    let mut twice_area: i64 = 0;
+    for i in 0..len {
+        let a = &points[i];
+        let b = &points[(i + 1) % len];
+        let (ax, ay) = (a.x as i64, a.y as i64);
+        let (bx, by) = (b.x as i64, b.y as i64);
+        twice_area += ax * by - bx * ay;
+    }
+    twice_area
+}
+
+// How the outline turns at a red tile
+//
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CornerKind {
+    // turns the same way as the outline as a whole, so the inside
+    // of the corner is inside the outline
+    Convex,
+    // turns against the outline, so the corner cuts into it
+    Concave,
+    // does not turn; the tile is partway along a straight line
+    Straight,
+}
+
+// Classify the corner at each red tile, in order, by the turn from
+// the segment coming in to the segment going out, compared with
+// the way the whole outline turns. For a simple closed outline
+// there are always four more convex corners than concave ones.
+// An outline enclosing no area has no way to turn, so its turning
+// corners are classified as though it turned counterclockwise.
+//
+#[allow(dead_code)]
+fn classify_corners(points: &[Point]) -> Vec<CornerKind> {
+    let len = points.len();
+    let orientation = match twice_signed_area(points).signum() {
+        0 => 1,
+        sign => sign,
+    };
+    let mut corners: Vec<CornerKind> = Vec::new();
+    for i in 0..len {
+        let prev = &points[(i + len - 1) % len];
+        let next = &points[(i + 1) % len];
+        let turn = turn_direction(prev, &points[i], next);
+        if 0 == turn {
+            corners.push(CornerKind::Straight);
+        } else if orientation == turn {
+            corners.push(CornerKind::Convex);
+        } else {
+            corners.push(CornerKind::Concave);
+        }
+    }
+    corners
+}
+
+// Summarize the red tiles and their outline, one line per fact
+//
+fn outline_stats(points: &[Point]) -> Vec<String> {
+    let mut grid = TileGrid::new();
+    for p in points.iter() {
+        grid.insert_red_tile(p);
+    }
+    let bounds = match grid.bounding_box() {
+        Some((ul, br)) => {
+            format!("{} to {}", ul.display(), br.display())
+        }
+        None => "none".to_string(),
+    };
+    let validation = validate_outline(points);
+    vec![
+        format!("Red tiles: {}", points.len()),
+        format!("Bounding box: {}", bounds),
+        format!("Closed: {}", validation.closed),
+        format!("Simple: {}", validation.simple),
+        format!("Perimeter: {}", outline_perimeter(points)),
+    ]
+}
+
+// Check that the red tiles can be filled: filling by a rule needs
+// a closed loop, and filling by marking the inside of each red
+// tile also needs the loop not to cross itself.
+//
+fn check_outline(
+    points: &[Point],
+    rule: Option<FillRule>,
+) -> Result<(), OutlineError> {
+    let validation = validate_outline(points);
+    if !validation.closed {
+        return Err(OutlineError::NotClosed);
+    }
+    if rule.is_none() && !validation.simple {
+        return Err(OutlineError::NotSimple);
+    }
+    Ok(())
+}
+
+// Solve part 2 in stages: outline the red tiles with green tiles,
+// fill in the outline, then find the largest filled rectangle
+// with red tiles as opposite corners. How long each stage took,
+// the outlined grid (when small) and the interior area are
+// written to `log`; the outlined and filled grids are also
+// written to `render`, if given. Nothing else is written.
+//
+// Returns the filled grid and the largest area. The outline must
+// already have passed check_outline.
+//
+fn solve_part2<L: Write>(
+    points: &[Point],
+    rule: Option<FillRule>,
+    include_boundary: bool,
+    mut render: Option<&mut File>,
+    log: &mut L,
+) -> std::io::Result<(TileGrid, u64)> {
+    let now = Instant::now();
+    let mut grid = TileGrid::outlined(points);
+    writeln!(
+        log,
+        "outlining red tiles took {} secs",
+        now.elapsed().as_secs_f64()
+    )?;
+    writeln!(log, "\nOUTLINED:")?;
+    if let Some((_, br)) = grid.bounding_box()
+        && br.x < 50
+        && br.y < 50
+    {
+        grid.write_render(log)?;
+    }
+    if let Some(rf) = render.as_mut() {
+        writeln!(rf, "OUTLINED:")?;
+        grid.write_render(rf)?;
+    }
+
+    let now = Instant::now();
+    grid.fill(points, rule);
+    writeln!(
+        log,
+        "filling by {} took {} secs",
+        match rule {
+            Some(rule) => format!("the {:?} rule", rule),
+            None => "marking the inside".to_string(),
+        },
+        now.elapsed().as_secs_f64()
+    )?;
+    writeln!(log, "Interior area: {}", grid.interior_area())?;
+    if let Some(rf) = render.as_mut() {
+        writeln!(rf, "\nFILLED:")?;
+        grid.write_render(rf)?;
+    }
+
+    let now = Instant::now();
+    let filled = FilledPrefixSum::new(&grid, include_boundary);
+    writeln!(
+        log,
+        "building the filled prefix sum took {} secs",
+        now.elapsed().as_secs_f64()
+    )?;
+
+    let now = Instant::now();
+    let max_area = find_max_filled_area_parallel(&filled, points);
+    writeln!(
+        log,
+        "find_max_filled_area_parallel() took {} secs",
+        now.elapsed().as_secs_f64()
+    )?;
+    Ok((grid, max_area))
+}
+
+// Solve part 2 through the same stages as solve_part2, but
+// without timing them or writing anything. Test-only; main keeps
+// the timed, logged solve_part2.
+//
+// Returns an error if the red tiles do not outline a closed,
+// simple loop.
+//
+#[cfg(test)]
+fn solve_part2_quiet(
+    points: &[Point],
+    include_boundary: bool,
+) -> Result<u64, OutlineError> {
+    check_outline(points, None)?;
+    let mut grid = TileGrid::outlined(points);
+    grid.fill(points, None);
+    let filled = FilledPrefixSum::new(&grid, include_boundary);
+    Ok(find_max_filled_area_parallel(&filled, points))
+}
+
+fn find_max_area(
+    max_area: &mut u64,
+    points: &Vec<Point>,
+    rng: Range<usize>,
+) {
+    let id_a: usize = rng.start;
+    let end: usize = rng.end;
+    if 1 >= (end - id_a) {
+        return;
+    }
+    let start = id_a + 1;
+    find_max_area(max_area, points, start..end);
+    let point_a = points.get(id_a).unwrap();
+    for id_b in start..end {
+        let point_b = points.get(id_b).unwrap();
+        let area = point_a.area_with(point_b);
+        if area > *max_area {
+            *max_area = area
+        }
+    }
+}
+
+fn file_to_points(f: File) -> Vec<Point> {
+    let rdr = BufReader::new(f);
+    let lines = rdr.lines();
+    let mut points: Vec<Point> = Vec::new();
+    let re_coord =
+        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*$").unwrap();
+    let mut line_num: usize = 0;
+    for line in lines {
+        line_num += 1;
+        let line = line.unwrap();
+        let line = line.trim();
+        if 0 == line.len() {
+            continue;
+        }
+        if !re_coord.is_match(&line) {
+            println!(
+                "*** FAILED *** to match line {}: '{}'",
+                line_num, line
+            );
+            continue;
+        }
+        let coords = re_coord.captures(&line).unwrap();
+        let xs = coords.get(1).unwrap().as_str();
+        let x = xs.parse::<u64>().unwrap();
+        let ys = coords.get(2).unwrap().as_str();
+        let y = ys.parse::<u64>().unwrap();
+        let p = Point::new(x, y);
+        points.push(p);
+    }
+    points
+}
+
+fn string_to_points(raw_input: String) -> Vec<Point> {
+    let mut points: Vec<Point> = Vec::new();
+    let re_coord =
+        Regex::new(r"^\s*([0-9]+)\s*,\s*([0-9]+)\s*$").unwrap();
+    let input = raw_input.as_str();
+    let lines = input.split('\n');
+    let mut line_num: usize = 0;
+    for line in lines {
+        line_num += 1;
+        let line = line.trim();
+        if 0 == line.len() {
+            continue;
+        }
+        if !re_coord.is_match(&line) {
+            println!(
+                "*** FAILED *** to match line {}: '{}'",
+                line_num, line
+            );
+            continue;
+        }
+        let coords = re_coord.captures(&line).unwrap();
+        let xs = coords.get(1).unwrap().as_str();
+        let x = xs.parse::<u64>().unwrap();
+        let ys = coords.get(2).unwrap().as_str();
+        let y = ys.parse::<u64>().unwrap();
+        let p = Point::new(x, y);
+        points.push(p);
+    }
+    points
+}
+
+// Binary crate entry point
+//
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let mut upto: usize = 10;
+    let path = &args.path;
+    let consider_green_tiles = &args.with_green_tiles;
+    let render_path = &args.render_path;
+
+    let f = File::open(path).with_context(|| {
+        format!("Could not open `{}`", path.display())
+    })?;
 
     let now = Instant::now();
     let points = file_to_points(f);
@@ -1197,6 +2227,13 @@ fn main() -> Result<()> {
         now.elapsed().as_secs_f64()
     );
 
+    if args.stats {
+        for line in outline_stats(&points) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
     if !*consider_green_tiles {
         let mut max_area: u64 = 0;
         let len = points.len();
@@ -1209,99 +2246,90 @@ fn main() -> Result<()> {
 
         println!("Max area: {}", max_area);
     } else {
-        let mut grid = TileGrid::new();
-
-        let now = Instant::now();
-        let len = points.len();
-        for i in 0..len {
-            let p: &Point = points.get(i).unwrap();
-            grid.insert_red_tile(points.get(i).unwrap());
+        check_outline(&points, args.fill_rule).with_context(|| {
+            format!(
+                "The red tiles in `{}` cannot be filled",
+                path.display()
+            )
+        })?;
+        let mut render_file = match render_path {
+            Some(rp) => Some(File::create(rp).with_context(|| {
+                format!("Could not create `{}`", rp.display())
+            })?),
+            None => None,
+        };
+        let (grid, max_area) = solve_part2(
+            &points,
+            args.fill_rule,
+            args.include_boundary,
+            render_file.as_mut(),
+            &mut std::io::stdout(),
+        )?;
+        if let Some(ip) = &args.interior_path {
+            let mut inf = File::create(ip).with_context(|| {
+                format!("Could not create `{}`", ip.display())
+            })?;
+            grid.write_interior(&mut inf)?;
         }
-        println!(
-            "inserting red tiles took {} secs",
-            now.elapsed().as_secs_f64()
-        );
 
-        let now = Instant::now();
-        let mut a = 0;
-        for next in 1..=len {
-            let mut b = next;
-            if next == len {
-                b = 0;
-            }
-            // println!(
-            //     "connecting {} --> {}",
-            //     points.get(a).unwrap().display(),
-            //     points.get(b).unwrap().display()
-            // );
-            grid.connect_red_tiles_with_green_tiles(
-                points.get(a).unwrap(),
-                points.get(b).unwrap(),
-            );
-            a = b;
-        }
-        println!(
-            "connecting red tiles took {} secs",
-            now.elapsed().as_secs_f64()
-        );
-        println!("\nOUTLINED:");
-        if grid.max_x < 50 && grid.max_y < 50 {
-            grid.display_grid();
-        }
+        println!("Max area: {}", max_area);
+    }
 
-        let now = Instant::now();
-        let mut a = 0;
-        for next in 1..=len {
-            let mut b = next;
-            if next == len {
-                b = 0;
-            }
-            let p_a = points.get(a).unwrap();
-            let p_b = points.get(b).unwrap();
-            // println!(
-            //     "marking {} ({:?})--> {}",
-            //     points.get(a).unwrap().display(),
-            //     grid.get_inside_direction(p_a.x, p_a.y),
-            //     points.get(b).unwrap().display()
-            // );
-            grid.mark_red_tiles_with_inside_direction(
-                points.get(a).unwrap(),
-                points.get(b).unwrap(),
-            );
-            // println!(
-            //     "a is now {:?}; b is now {:?}",
-            //     grid.get_inside_direction(p_a.x, p_a.y),
-            //     grid.get_inside_direction(p_b.x, p_b.y)
-            // );
-            a = b;
-        }
-        println!(
-            "marking inside orientation of red tiles took {} secs",
-            now.elapsed().as_secs_f64()
-        );
+    Ok(())
+}
 
-        let now = Instant::now();
-        grid.fill_in_loops();
-        // println!("\nFILLED:");
-        // grid.display_grid();
-        println!(
-            "filling loops took {} secs",
-            now.elapsed().as_secs_f64()
-        );
+#[cfg(test)]
+fn testhelper_hash_of(p: &Point) -> u64 {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    p.hash(&mut hasher);
+    hasher.finish()
+}
 
-        let now = Instant::now();
-        let mut max_area: u64 = 0;
-        let len = points.len();
-        grid.find_max_filled_area(&mut max_area, &points, 0..len);
-        println!(
-            "find_max_filled_area() took {} secs",
-            now.elapsed().as_secs_f64()
-        );
+// the example from the puzzle
+//
+#[cfg(test)]
+const GIVEN_EXAMPLE: &str = "7,1
+11,1
+11,7
+9,7
+9,5
+2,5
+2,3
+7,3";
 
-        println!("Max area: {}", max_area);
-    }
+// a less regular example, with a notch in the top of its outline
+// and steps in the bottom
+//
+#[cfg(test)]
+const DEGEN_EXAMPLE: &str = "3,1
+6,1
+6,3
+11,3
+11,1
+15,1
+15,5
+9,5
+9,6
+6,6
+6,8
+1,8
+1,5
+3,5";
 
-    Ok(())
+#[test]
+fn t_point_eq_and_hash() {
+    let a = Point::new(7, 11);
+    let b = Point::new(7, 11);
+    let c = Point::new(11, 7);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(testhelper_hash_of(&a), testhelper_hash_of(&b));
+
+    let mut seen = std::collections::HashSet::new();
+    seen.insert(a);
+    assert!(seen.contains(&b));
+    assert!(!seen.contains(&c));
 }
 
 #[test]
@@ -1354,16 +2382,7 @@ fn t_br_ul() {
 
 #[test]
 fn t_given_example_part1() {
-    let raw_input = "7,1
-11,1
-11,7
-9,7
-9,5
-2,5
-2,3
-7,3"
-    .to_string();
-    let points = string_to_points(raw_input);
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
 
     let mut max_area: u64 = 0;
     let len = points.len();
@@ -1374,26 +2393,43 @@ fn t_given_example_part1() {
 
 #[test]
 fn t_given_example_part2() {
-    let raw_input = "7,1
-11,1
-11,7
-9,7
-9,5
-2,5
-2,3
-7,3"
-    .to_string();
-    let points = string_to_points(raw_input);
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
 
-    let mut grid = TileGrid::new();
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    println!("\nFILLED:");
+    grid.display_grid();
 
+    let mut max_area: u64 = 0;
+    let len = points.len();
+    grid.find_max_filled_area(&mut max_area, &points, 0..len, false);
+
+    assert_eq!(24, max_area);
+}
+
+#[test]
+fn t_degen_example_part_2() {
+    let points = string_to_points(DEGEN_EXAMPLE.to_string());
+
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    println!("\nFILLED:");
+    grid.display_grid();
+
+    let mut max_area: u64 = 0;
+    let len = points.len();
+    grid.find_max_filled_area(&mut max_area, &points, 0..len, false);
+
+    assert_eq!(32, max_area);
+}
+
+#[test]
+fn t_write_render_matches_render() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+
+    let mut grid = TileGrid::new();
     let len = points.len();
     for i in 0..len {
-        let p: &Point = points.get(i).unwrap();
-        println!("About to insert ({},{})", p.x, p.y);
         grid.insert_red_tile(points.get(i).unwrap());
     }
-
     let mut a = 0;
     for next in 1..=len {
         let mut b = next;
@@ -1406,98 +2442,499 @@ fn t_given_example_part2() {
         );
         a = b;
     }
-    println!("\nOUTLINED:");
-    grid.display_grid();
 
-    let mut a = 0;
-    for next in 1..=len {
-        let mut b = next;
-        if next == len {
-            b = 0;
+    let mut buf: Vec<u8> = Vec::new();
+    grid.write_render(&mut buf).unwrap();
+    let written = String::from_utf8(buf).unwrap();
+    assert_eq!(grid.render(), written);
+    let lines: Vec<&str> = written.lines().collect();
+    assert_eq!(8, lines.len());
+    assert_eq!(".......#XXX#", lines[1]);
+}
+
+#[test]
+fn t_empty_grid() {
+    let grid = TileGrid::new();
+    assert!(grid.is_empty());
+    assert!(grid.bounding_box().is_none());
+    assert_eq!("", grid.render());
+}
+
+#[test]
+fn t_one_tile_grid() {
+    let mut grid = TileGrid::new();
+    grid.insert_red_tile(&Point::new(3, 2));
+    assert!(!grid.is_empty());
+    let (ul, br) = grid.bounding_box().unwrap();
+    assert_eq!((3, 2), (ul.x, ul.y));
+    assert_eq!((3, 2), (br.x, br.y));
+    assert_eq!("....\n....\n...#\n", grid.render());
+}
+
+#[test]
+fn t_interior_area_matches_shoelace() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+
+    // the polygon area by the shoelace formula, and the number
+    // of tiles on the outline
+    //
+    let area = polygon_area(&points);
+    let boundary = outline_perimeter(&points);
+    assert_eq!(30, area);
+    assert_eq!(30, boundary);
+
+    // by Pick's theorem, interior = area - boundary/2 + 1
+    //
+    let expected: u64 = area - boundary / 2 + 1;
+    assert_eq!(16, expected);
+    assert_eq!(expected, grid.interior_area());
+    assert_eq!(46, area + boundary / 2 + 1);
+    assert_eq!(46, grid.interior_area() + boundary);
+}
+
+#[test]
+fn t_polygon_area_degen_example() {
+    let points = string_to_points(DEGEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    let area = polygon_area(&points);
+    let boundary = outline_perimeter(&points);
+    assert_eq!(56, area);
+    assert_eq!(46, boundary);
+    assert_eq!(80, area + boundary / 2 + 1);
+    assert_eq!(80, grid.interior_area() + boundary);
+
+    // the order the outline is walked in doesn't matter
+    //
+    let reversed: Vec<Point> = points.iter().rev().copied().collect();
+    assert_eq!(area, polygon_area(&reversed));
+}
+
+#[test]
+fn t_classify_corners_degen_example() {
+    let points = string_to_points(DEGEN_EXAMPLE.to_string());
+    let corners = classify_corners(&points);
+    let concave_at: Vec<usize> = vec![2, 3, 7, 9, 13];
+    for (i, corner) in corners.iter().enumerate() {
+        if concave_at.contains(&i) {
+            assert_eq!(CornerKind::Concave, *corner, "corner {}", i);
+        } else {
+            assert_eq!(CornerKind::Convex, *corner, "corner {}", i);
         }
-        grid.mark_red_tiles_with_inside_direction(
-            points.get(a).unwrap(),
-            points.get(b).unwrap(),
+    }
+    let count = |kind: CornerKind| -> i64 {
+        corners.iter().filter(|x| **x == kind).count() as i64
+    };
+    assert_eq!(9, count(CornerKind::Convex));
+    assert_eq!(
+        4,
+        count(CornerKind::Convex) - count(CornerKind::Concave)
+    );
+
+    // walking the outline the other way keeps each corner's kind
+    //
+    let reversed: Vec<Point> = points.iter().rev().copied().collect();
+    let mut reversed_corners = classify_corners(&reversed);
+    reversed_corners.reverse();
+    assert_eq!(corners, reversed_corners);
+
+    // a red tile partway along a side is not a corner at all
+    //
+    let mut with_straight = points.clone();
+    with_straight.insert(1, Point::new(4, 1));
+    let corners = classify_corners(&with_straight);
+    assert_eq!(CornerKind::Straight, corners[1]);
+    assert_eq!(CornerKind::Convex, corners[2]);
+}
+
+#[test]
+fn t_parallel_max_filled_area_matches_sequential() {
+    fn assert_sync<T: Sync>() {}
+    assert_sync::<FilledPrefixSum>();
+
+    for (raw_input, expected) in
+        [(GIVEN_EXAMPLE, 24), (DEGEN_EXAMPLE, 32)]
+    {
+        let points = string_to_points(raw_input.to_string());
+        let grid = TileGrid::from_closed_polygon(&points).unwrap();
+        let mut sequential: u64 = 0;
+        grid.find_max_filled_area(
+            &mut sequential,
+            &points,
+            0..points.len(),
+            false,
         );
-        a = b;
+        let filled = FilledPrefixSum::new(&grid, false);
+        let parallel = find_max_filled_area_parallel(&filled, &points);
+        assert_eq!(expected, sequential);
+        assert_eq!(sequential, parallel);
     }
-    println!("\nMARKED:");
+}
 
-    grid.fill_in_loops();
-    println!("\nFILLED:");
-    grid.display_grid();
+#[test]
+fn t_outline_is_simple() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    assert!(outline_is_simple(&points));
 
-    let mut max_area: u64 = 0;
-    let len = points.len();
-    grid.find_max_filled_area(&mut max_area, &points, 0..len);
+    // a figure-eight, whose first and fourth segments cross at (3,3)
+    //
+    let raw_input = "1,3
+5,3
+5,5
+3,5
+3,1
+1,1"
+    .to_string();
+    let points = string_to_points(raw_input);
+    assert!(!outline_is_simple(&points));
+}
 
-    assert_eq!(24, max_area);
+#[test]
+fn t_outline_stats() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let expected = vec![
+        "Red tiles: 8",
+        "Bounding box: (2,1) to (11,7)",
+        "Closed: true",
+        "Simple: true",
+        "Perimeter: 30",
+    ];
+    assert_eq!(expected, outline_stats(&points));
+
+    // dropping a corner leaves a diagonal step
+    //
+    let open: Vec<Point> = points.iter().skip(1).copied().collect();
+    assert!(!validate_outline(&open).closed);
 }
 
 #[test]
-fn t_degen_example_part_2() {
-    let raw_input = "3,1
-6,1
-6,3
-11,3
-11,1
-15,1
-15,5
+fn t_from_closed_polygon() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    assert_eq!(16, grid.interior_area());
+
+    // dropping a corner leaves a diagonal step
+    //
+    let open: Vec<Point> = points.iter().skip(1).copied().collect();
+    assert_eq!(
+        Some(OutlineError::NotClosed),
+        TileGrid::from_closed_polygon(&open).err()
+    );
+
+    // a figure-eight
+    //
+    let raw_input = "1,3
+5,3
+5,5
+3,5
+3,1
+1,1"
+    .to_string();
+    let points = string_to_points(raw_input);
+    assert_eq!(
+        Some(OutlineError::NotSimple),
+        TileGrid::from_closed_polygon(&points).err()
+    );
+}
+
+#[test]
+fn t_fill_rules() {
+    // on a simple loop, both rules fill what from_closed_polygon
+    // fills
+    //
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let expected = TileGrid::from_closed_polygon(&points).unwrap();
+    for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+        let grid =
+            TileGrid::from_outline_with_rule(&points, rule).unwrap();
+        assert_eq!(expected.render(), grid.render());
+    }
+
+    // a loop that winds twice around 5..=7,3..=5
+    //
+    let raw_input = "0,0
+8,0
+8,6
+4,6
+4,2
+10,2
+10,10
+0,10"
+        .to_string();
+    let points = string_to_points(raw_input);
+    let even_odd =
+        TileGrid::from_outline_with_rule(&points, FillRule::EvenOdd)
+            .unwrap();
+    let nonzero =
+        TileGrid::from_outline_with_rule(&points, FillRule::NonZero)
+            .unwrap();
+    assert!(even_odd.is_color_green_fill(2, 4));
+    assert!(nonzero.is_color_green_fill(2, 4));
+    assert!(even_odd.is_color_other(6, 4));
+    assert!(nonzero.is_color_green_fill(6, 4));
+    assert!(even_odd.is_color_other(9, 1));
+    assert!(nonzero.is_color_other(9, 1));
+    assert_eq!(9, nonzero.interior_area() - even_odd.interior_area());
+}
+
+#[test]
+fn t_add_red_and_refill_region() {
+    // start from the first six tiles of the degenerate example,
+    // which close on themselves along row 1, and add the rest one
+    // at a time
+    //
+    let points = string_to_points(DEGEN_EXAMPLE.to_string());
+    for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+        let mut grid =
+            TileGrid::from_outline_with_rule(&points[..6], rule)
+                .unwrap();
+        for p in &points[6..] {
+            grid.add_red_and_refill_region(p);
+        }
+        let expected =
+            TileGrid::from_outline_with_rule(&points, rule).unwrap();
+        assert_eq!(expected.render(), grid.render());
+        assert_eq!(expected.interior_area(), grid.interior_area());
+    }
+}
+
+#[test]
+fn t_add_red_and_refill_region_closed_polygon() {
+    // a grid from from_closed_polygon has no fill rule, so the
+    // region is refilled even-odd; start from the first four tiles,
+    // a rectangle closing along column 5, and add the rest
+    //
+    let raw_input = "5,5
 9,5
-9,6
-6,6
-6,8
-1,8
-1,5
-3,5"
+9,2
+5,2
+5,1
+2,1
+2,8
+5,8"
     .to_string();
     let points = string_to_points(raw_input);
+    let mut grid = TileGrid::from_closed_polygon(&points[..4]).unwrap();
+    for p in &points[4..] {
+        grid.add_red_and_refill_region(p);
+    }
+    let expected = TileGrid::from_closed_polygon(&points).unwrap();
+    assert_eq!(expected.render(), grid.render());
+    assert_eq!(expected.interior_area(), grid.interior_area());
+    assert_eq!(expected.bounding_box(), grid.bounding_box());
+}
 
+#[test]
+fn t_remove_tile_shrinks_bounds() {
     let mut grid = TileGrid::new();
+    for (x, y) in [(2, 3), (4, 3), (4, 6), (7, 1)] {
+        grid.insert_red_tile(&Point::new(x, y));
+    }
+    assert_eq!(
+        Some((Point::new(2, 1), Point::new(7, 6))),
+        grid.bounding_box()
+    );
+    grid.remove_tile(7, 1);
+    assert_eq!(
+        Some((Point::new(2, 3), Point::new(4, 6))),
+        grid.bounding_box()
+    );
+    grid.remove_tile(4, 6);
+    grid.remove_tile(9, 9);
+    assert_eq!(
+        Some((Point::new(2, 3), Point::new(4, 3))),
+        grid.bounding_box()
+    );
+    grid.remove_tile(2, 3);
+    grid.remove_tile(4, 3);
+    assert_eq!(None, grid.bounding_box());
+}
 
-    let len = points.len();
-    for i in 0..len {
-        let p: &Point = points.get(i).unwrap();
-        println!("About to insert ({},{})", p.x, p.y);
-        grid.insert_red_tile(points.get(i).unwrap());
+#[test]
+fn t_fill_is_stable() {
+    for raw_input in [GIVEN_EXAMPLE, DEGEN_EXAMPLE] {
+        let points = string_to_points(raw_input.to_string());
+        let grid = TileGrid::from_closed_polygon(&points).unwrap();
+        assert!(0 < grid.cell_census().2);
+        assert!(grid.fill_is_stable());
+        for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            let grid = TileGrid::from_outline_with_rule(&points, rule)
+                .unwrap();
+            assert!(grid.fill_is_stable());
+        }
     }
+}
 
-    let mut a = 0;
-    for next in 1..=len {
-        let mut b = next;
-        if next == len {
-            b = 0;
+#[test]
+fn t_write_interior() {
+    use std::collections::BTreeSet;
+
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+
+    let mut buf: Vec<u8> = Vec::new();
+    grid.write_interior(&mut buf).unwrap();
+    let written = String::from_utf8(buf).unwrap();
+    let actual: BTreeSet<(u64, u64)> = written
+        .lines()
+        .map(|line| {
+            let (x, y) = line.split_once(',').unwrap();
+            (x.parse().unwrap(), y.parse().unwrap())
+        })
+        .collect();
+
+    let mut expected: BTreeSet<(u64, u64)> = BTreeSet::new();
+    for y in 2..=3 {
+        for x in 8..=10 {
+            expected.insert((x, y));
         }
-        grid.connect_red_tiles_with_green_tiles(
-            points.get(a).unwrap(),
-            points.get(b).unwrap(),
+    }
+    for x in 3..=10 {
+        expected.insert((x, 4));
+    }
+    expected.insert((10, 5));
+    expected.insert((10, 6));
+    assert_eq!(16, expected.len());
+    assert_eq!(expected, actual);
+    assert_eq!(grid.interior_area() as usize, written.lines().count());
+}
+
+#[test]
+fn t_include_boundary() {
+    // the given example has no rectangle with outline tiles inside
+    // it that stays within the outline, so both definitions agree
+    //
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    for include_boundary in [false, true] {
+        let mut max_area: u64 = 0;
+        grid.find_max_filled_area(
+            &mut max_area,
+            &points,
+            0..points.len(),
+            include_boundary,
         );
-        a = b;
+        assert_eq!(24, max_area);
     }
-    println!("\nOUTLINED:");
-    grid.display_grid();
 
-    let mut a = 0;
-    for next in 1..=len {
-        let mut b = next;
-        if next == len {
-            b = 0;
-        }
-        grid.mark_red_tiles_with_inside_direction(
-            points.get(a).unwrap(),
-            points.get(b).unwrap(),
+    // a notch in the top edge puts outline tiles inside the
+    // rectangle from (1,1) to (7,5)
+    //
+    // #X#.#X#
+    // X@#X#@X
+    // X@@@@@X
+    // X@@@@@X
+    // #XXXXX#
+    //
+    let raw_input = "1,1
+3,1
+3,2
+5,2
+5,1
+7,1
+7,5
+1,5"
+    .to_string();
+    let points = string_to_points(raw_input);
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    for (include_boundary, expected) in [(false, 20), (true, 35)] {
+        let mut max_area: u64 = 0;
+        grid.find_max_filled_area(
+            &mut max_area,
+            &points,
+            0..points.len(),
+            include_boundary,
+        );
+        assert_eq!(expected, max_area);
+        let filled = FilledPrefixSum::new(&grid, include_boundary);
+        assert_eq!(
+            expected,
+            find_max_filled_area_parallel(&filled, &points)
         );
-        a = b;
     }
-    println!("\nMARKED:");
+}
 
-    grid.fill_in_loops();
-    println!("\nFILLED:");
-    grid.display_grid();
+#[test]
+fn t_maximal_filled_rectangles() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
+    let actual: Vec<_> = grid
+        .maximal_filled_rectangles(&points, false)
+        .iter()
+        .map(|(ul, br, area)| ((ul.x, ul.y), (br.x, br.y), *area))
+        .collect();
+    let expected = vec![
+        ((2, 3), (9, 5), 24),
+        ((7, 1), (9, 5), 15),
+        ((7, 1), (11, 3), 15),
+        ((9, 1), (11, 7), 21),
+    ];
+    assert_eq!(expected, actual);
+}
 
-    let mut max_area: u64 = 0;
-    let len = points.len();
-    grid.find_max_filled_area(&mut max_area, &points, 0..len);
+#[test]
+fn t_filled_in_rect() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    let grid = TileGrid::from_closed_polygon(&points).unwrap();
 
-    assert_eq!(32, max_area);
+    // the whole bounding box holds the whole interior, and so
+    // does a rectangle reaching beyond it
+    //
+    let ul = Point::new(2, 1);
+    let br = Point::new(11, 7);
+    assert_eq!(16, grid.filled_in_rect(&ul, &br));
+    assert_eq!(
+        16,
+        grid.filled_in_rect(&Point::new(0, 0), &Point::new(20, 20))
+    );
+
+    // a sub-rectangle holds part of the interior: one tile in
+    // each of rows 2 and 3, and six in row 4; the corners can be
+    // given in either order
+    //
+    let a = Point::new(0, 0);
+    let b = Point::new(8, 4);
+    assert_eq!(8, grid.filled_in_rect(&a, &b));
+    assert_eq!(8, grid.filled_in_rect(&b, &a));
+    assert_eq!(
+        8,
+        grid.filled_in_rect(&Point::new(8, 0), &Point::new(0, 4))
+    );
+
+    // a rectangle off the grid holds none
+    //
+    assert_eq!(
+        0,
+        grid.filled_in_rect(&Point::new(12, 0), &Point::new(20, 5))
+    );
+}
+
+#[test]
+fn t_solve_part2_quiet() {
+    let points = string_to_points(GIVEN_EXAMPLE.to_string());
+    assert_eq!(Ok(24), solve_part2_quiet(&points, false));
+
+    // solve_part2 takes the same stages, writing only to the log
+    // it is given, and agrees with the quiet version
+    //
+    let mut log: Vec<u8> = Vec::new();
+    let (grid, max_area) =
+        solve_part2(&points, None, false, None, &mut log).unwrap();
+    assert_eq!(24, max_area);
+    let log = String::from_utf8(log).unwrap();
+    assert!(log.contains(&TileGrid::outlined(&points).render()));
+    assert!(log.contains(&format!(
+        "Interior area: {}\n",
+        grid.interior_area()
+    )));
+    assert!(log.contains("find_max_filled_area_parallel() took"));
+
+    let points = string_to_points(DEGEN_EXAMPLE.to_string());
+    assert_eq!(Ok(32), solve_part2_quiet(&points, false));
+
+    let open: Vec<Point> = points.iter().skip(1).copied().collect();
+    assert_eq!(
+        Err(OutlineError::NotClosed),
+        solve_part2_quiet(&open, false)
+    );
 }